@@ -0,0 +1,59 @@
+use std::time::Instant;
+
+use codewars::eval_expression::{calc, compile};
+
+const EXPR: &str = "x * sin(y) + (x - 1) ^ 2 / max(y, 1)";
+
+fn main() {
+    let Some(arg) = std::env::args().nth(1) else {
+        eprintln!("Expected natural number N as an argument");
+        std::process::exit(1);
+    };
+
+    let Ok(n) = arg.parse::<usize>() else {
+        eprintln!("Expected natural number, got {arg}");
+        std::process::exit(2);
+    };
+
+    let inputs = (0..n)
+        .map(|i| (i as f64 / 10.0, (i % 100) as f64 / 7.0))
+        .collect::<Vec<_>>();
+
+    // calc has no variables, so the inputs have to be formatted into the expression
+    let exprs = inputs
+        .iter()
+        .map(|(x, y)| format!("{x} * sin({y}) + ({x} - 1) ^ 2 / max({y}, 1)"))
+        .collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let expected = exprs.iter().map(|expr| calc(expr)).collect::<Vec<_>>();
+    let elapsed_calc = start.elapsed();
+
+    let start = Instant::now();
+    let compiled = compile(EXPR, &["x", "y"]).expect("valid expression");
+    let elapsed_compile = start.elapsed();
+
+    let start = Instant::now();
+    let actual = inputs
+        .iter()
+        .map(|&(x, y)| compiled.eval(&[x, y]))
+        .collect::<Vec<_>>();
+    let elapsed_compiled = start.elapsed();
+
+    // Compare up to rounding, compiled code is not bound to the evaluation order of calc
+    for ((x, y), (expected, actual)) in inputs.iter().zip(expected.iter().zip(&actual)) {
+        assert!(
+            (expected - actual).abs() <= 1e-9 * expected.abs().max(1.0),
+            "compiled result {actual} differs from calc {expected} at x = {x}, y = {y}"
+        );
+    }
+
+    println!("Evaluated '{EXPR}' {n} times");
+    println!(" - calc:     {elapsed_calc:?}");
+    println!(" - compile:  {elapsed_compile:?}");
+    println!(" - compiled: {elapsed_compiled:?}");
+    println!(
+        " - speedup:  {:.1}x",
+        elapsed_calc.as_secs_f64() / elapsed_compiled.as_secs_f64()
+    );
+}
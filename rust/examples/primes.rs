@@ -6,12 +6,12 @@ fn main() {
         std::process::exit(1);
     };
 
-    let Ok(n) = arg.parse::<usize>() else {
+    let Ok(n) = arg.parse::<u64>() else {
         eprintln!("Expected natural number, got {arg}");
         std::process::exit(2);
     };
 
-    if let Some(p) = primes::stream().nth(n.saturating_sub(1)) {
+    if let Some(p) = primes::nth_prime(n.max(1)) {
         println!("The {n}-th prime is {p}");
     }
 }
@@ -1,129 +1,40 @@
-use std::fmt::Write as _;
-
-use num::integer::binomial;
-use once_cell::sync::Lazy;
-use regex::Regex;
-
-static EXPR_PATTER: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"\((?P<m>-)?(?P<a>\d+)?(?P<x>[a-zA-z])(?P<o>[+-])(?P<b>\d+)\)\^(?P<n>\d+)").unwrap()
-});
-
+//! Expansion of products and powers of polynomials, e.g. `(x+2y-3)^5*(x-1)`
+//!
+//! Expressions are parsed into a sparse multivariate [Polynomial] with arbitrary-precision
+//! coefficients, so that even binomials with large exponents expand without overflow. Conversely,
+//! [factor] splits an expanded polynomial in one variable into linear factors of its rational roots.
+
+mod factor;
+mod polynomial;
+mod univariate;
+
+pub use factor::{Factorization, MAX_CANDIDATES};
+pub use polynomial::{Monomial, Polynomial, MAX_POW_BITS};
+pub use univariate::Univariate;
+
+/// Expands given expression (e.g. `(ax+b)^n`) into a sum of terms in the descending order of their
+/// degrees, e.g. `x^2+2x+1`.
+///
+/// # Panics
+/// If the expression is not a valid polynomial expression (see [Polynomial]).
 pub fn expand(expr: &str) -> String {
-    let Ok(Expr { a, x, b, n }) = Expr::try_from(expr) else {
-        panic!("invalid expression: {expr}");
-    };
-
-    if a == 0 {
-        return format!("{}", b.pow(n));
-    }
-
-    if b == 0 {
-        return format!("{}", Term::init(a, x, n));
-    }
-
-    let mut expansion = String::new();
-
-    for term in (0..=n).map(|k| Term::new(k, a, x, b, n)) {
-        write!(&mut expansion, "{term}").expect("write term");
-    }
-
-    expansion
-}
-
-struct Expr<'a> {
-    a: i64,
-    x: &'a str,
-    b: i64,
-    n: u32,
-}
-
-impl<'a> TryFrom<&'a str> for Expr<'a> {
-    type Error = ();
-
-    /// Parse an expression of the form: `(ax+b)^n`
-    fn try_from(expr: &'a str) -> Result<Self, Self::Error> {
-        let Some(caps) = EXPR_PATTER.captures(expr) else {
-            return Err(());
-        };
-
-        let mut a = match caps.name("a") {
-            Some(a) => a.as_str().parse().map_err(|_| ())?,
-            None => 1,
-        };
-
-        if caps.name("m").is_some() {
-            a = -a;
-        }
-
-        let Some(x) = caps.name("x").map(|x| x.as_str()) else {
-            return Err(());
-        };
-
-        let mut b = match caps.name("b") {
-            Some(b) => b.as_str().parse().map_err(|_| ())?,
-            None => 0,
-        };
-
-        if let Some("-") = caps.name("o").map(|o| o.as_str()) {
-            b = -b;
-        }
-
-        let n = match caps.name("n") {
-            Some(n) => n.as_str().parse().map_err(|_| ())?,
-            None => 1,
-        };
-
-        Ok(Expr { a, x, b, n })
+    match expr.parse::<Polynomial>() {
+        Ok(p) => p.to_string(),
+        Err(e) => panic!("invalid expression: {expr} ({e})"),
     }
 }
 
-struct Term<'a> {
-    k: u32,
-    c: i64,
-    x: &'a str,
-    n: u32,
-}
-
-impl<'x> Term<'x> {
-    fn init(a: i64, x: &'x str, n: u32) -> Self {
-        Self {
-            k: 0,
-            c: a.pow(n),
-            x,
-            n,
-        }
-    }
-
-    fn new(k: u32, a: i64, x: &'x str, b: i64, n: u32) -> Self {
-        Self {
-            k,
-            c: binomial(n, k) as i64 * a.pow(n - k) * b.pow(k),
-            x,
-            n,
-        }
-    }
-}
-
-impl std::fmt::Display for Term<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.k > 0 && self.c > 0 {
-            f.write_char('+')?;
-        }
-
-        let i = self.n - self.k;
-
-        match (self.c, i) {
-            (c @ 0, _) | (c, 0) => write!(f, "{c}")?,
-            (1, _) => write!(f, "{}", self.x)?,
-            (-1, _) => write!(f, "-{}", self.x)?,
-            (c, _) => write!(f, "{c}{}", self.x)?,
-        }
-
-        if i > 1 {
-            write!(f, "^{i}")?;
-        }
-
-        Ok(())
+/// Factors an expression in one variable (e.g. `x^3-6x^2+11x-6`) into its content and linear
+/// factors of its rational roots, e.g. `(x-1)(x-2)(x-3)`, which is the inverse of [expand].
+///
+/// Factors without rational roots are left intact, e.g. `x^4-1` factors into `(x-1)(x+1)(x^2+1)`.
+///
+/// # Panics
+/// If the expression is not a valid polynomial expression in one variable.
+pub fn factor(expr: &str) -> String {
+    match expr.parse::<Univariate<_>>() {
+        Ok(p) => p.factor().to_string(),
+        Err(e) => panic!("invalid expression: {expr} ({e})"),
     }
 }
 
@@ -150,4 +61,26 @@ mod tests {
         let actual = expand(expr);
         assert_eq!(expected, actual);
     }
+
+    #[rstest]
+    #[case("(x+1)^2(x-1)", "x^3+x^2-x-1")]
+    #[case("(2a-b)^3", "8a^3-12a^2b+6ab^2-b^3")]
+    #[case("(x+y)^2*(x-y)^2", "x^4-2x^2y^2+y^4")]
+    #[trace]
+    fn products(#[case] expr: &str, #[case] expected: &str) {
+        assert_eq!(expected, expand(expr));
+    }
+
+    #[test]
+    fn no_overflow() {
+        let expansion = expand("(3x-2)^50");
+        assert!(expansion.starts_with("717897987691852588770249x^50-"));
+        assert!(expansion.ends_with("+1125899906842624"));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid expression: (x+1)^")]
+    fn invalid_expression() {
+        expand("(x+1)^");
+    }
 }
@@ -0,0 +1,250 @@
+//! Factoring of integer polynomials by the
+//! [rational root test](https://en.wikipedia.org/wiki/Rational_root_theorem)
+//!
+//! A rational root `p/q` (in lowest terms) of a polynomial with integer coefficients must have `p`
+//! dividing the constant coefficient and `q` dividing the leading one. Each such root splits off a
+//! linear factor `qx-p` by polynomial division, and whatever remains has no rational roots and is
+//! left intact (e.g. `x^2+1`).
+//!
+//! The divisors are enumerated from prime factorizations, so the search is limited to polynomials
+//! whose (square-free part has) constant and leading coefficients which fit into `u64` and which
+//! have at most [MAX_CANDIDATES] candidates for roots. Other polynomials are left intact.
+
+use super::Univariate;
+use crate::number_theory::factorize;
+use num::{BigInt, BigRational, One, Signed, ToPrimitive, Zero};
+use std::fmt::{Display, Write};
+
+/// Upper bound on the number of candidates for rational roots which are tested
+pub const MAX_CANDIDATES: usize = 1 << 16;
+
+/// Factorization of an integer polynomial into its content and primitive factors with their
+/// multiplicities
+///
+/// Displayed in the [expand](super::expand) format of the factors, e.g. `-2x(x-1)^2(x^2+1)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Factorization {
+    /// Content of the polynomial with the sign of its leading coefficient
+    pub constant: BigInt,
+    /// Linear factors ordered by the absolute values of their roots (positive roots first),
+    /// followed by the factor without rational roots (if any)
+    pub factors: Vec<(Univariate<BigInt>, u32)>,
+}
+
+impl Univariate<BigInt> {
+    /// Splits off all the linear factors of this polynomial which correspond to its rational roots
+    pub fn factor(&self) -> Factorization {
+        let constant = match self.leading() {
+            Some(lead) if lead.is_negative() => -self.content(),
+            Some(_) => self.content(),
+            None => BigInt::zero(),
+        };
+
+        let mut factors = Vec::new();
+        if self.degree().unwrap_or(0) == 0 {
+            return Factorization { constant, factors };
+        }
+
+        let var = self.var();
+        let p = self.primitive_part();
+
+        // Root `0` is factored out by dividing by the lowest power of `x`
+        let zeros = p.coefficients().iter().take_while(|a| a.is_zero()).count();
+        if zeros > 0 {
+            let x = Univariate::new(var, [BigInt::zero(), BigInt::one()]);
+            factors.push((x, zeros as u32));
+        }
+        let mut rest = Univariate::new(var, &p.coefficients()[zeros..]).to_rational();
+
+        // Roots are looked for in the square-free part, whose coefficients are much smaller for
+        // high multiplicities (e.g. `3x-2` instead of the expansion of `(3x-2)^34`)
+        let squarefree = if rest.degree() > Some(0) {
+            rest.div_rem(&rest.gcd(&rest.derivative())).0
+        } else {
+            rest.clone()
+        };
+
+        for root in candidates(&squarefree) {
+            let linear = Univariate::new(var, [-root.numer(), root.denom().clone()]);
+            let divisor = linear.to_rational();
+
+            let mut multiplicity = 0;
+            while rest.degree() > Some(0) && rest.eval(&root).is_zero() {
+                rest = rest.div_rem(&divisor).0;
+                multiplicity += 1;
+            }
+            if multiplicity > 0 {
+                factors.push((linear, multiplicity));
+            }
+        }
+
+        if rest.degree() > Some(0) {
+            factors.push((rest.to_integer(), 1));
+        }
+
+        Factorization { constant, factors }
+    }
+}
+
+/// Returns all the candidates `±p/q` for rational roots of given polynomial (with a non-zero
+/// constant coefficient) ordered by their absolute values, positive ones first
+///
+/// Candidates exceeding the [root bound](Univariate::root_bound) are left out. There are no
+/// candidates at all if the coefficients don't fit into `u64` or if there are more than
+/// [MAX_CANDIDATES] of them.
+fn candidates(poly: &Univariate<BigRational>) -> Vec<BigRational> {
+    let coefs = poly.to_integer();
+    let (Some(first), Some(last), Some(bound)) = (
+        coefs.coefficients().first(),
+        coefs.leading(),
+        poly.root_bound(),
+    ) else {
+        return Vec::new();
+    };
+    let (Some(ps), Some(qs)) = (divisors(first), divisors(last)) else {
+        return Vec::new();
+    };
+
+    let mut candidates = Vec::new();
+    for q in qs {
+        // Divisors are ascending, so all the following `p/q` exceed the bound as well
+        for p in &ps {
+            let r = BigRational::new(p.clone(), q.clone());
+            if r > bound {
+                break;
+            }
+            if candidates.len() == MAX_CANDIDATES {
+                return Vec::new();
+            }
+            candidates.push(r);
+        }
+    }
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    candidates
+        .into_iter()
+        .flat_map(|r| [r.clone(), -r])
+        .collect()
+}
+
+/// Returns positive divisors of a non-zero integer in the ascending order, or `None` if its
+/// absolute value does not fit into `u64`
+fn divisors(n: &BigInt) -> Option<Vec<BigInt>> {
+    let mut divisors = vec![1];
+
+    for (p, k) in factorize(n.abs().to_u64()?) {
+        let coprime = divisors.clone();
+        let mut pk = 1;
+        for _ in 0..k {
+            pk *= p;
+            divisors.extend(coprime.iter().map(|d| d * pk));
+        }
+    }
+
+    divisors.sort_unstable();
+    Some(divisors.into_iter().map(BigInt::from).collect())
+}
+
+impl Display for Factorization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { constant, factors } = self;
+
+        match factors[..] {
+            [] => return write!(f, "{constant}"),
+            // A single factor needs no parentheses, e.g. `x^2+1`
+            [(ref p, 1)] if constant.is_one() => return write!(f, "{p}"),
+            _ if constant.is_one() => {}
+            _ if (-constant).is_one() => f.write_char('-')?,
+            _ => write!(f, "{constant}")?,
+        }
+
+        for (p, n) in factors {
+            if p.coefficients().len() == 2 && p.coefficients()[0].is_zero() {
+                write!(f, "{p}")?;
+            } else {
+                write!(f, "({p})")?;
+            }
+            if *n > 1 {
+                write!(f, "^{n}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{expand, factor};
+    use super::*;
+    use rstest::rstest;
+    use std::time::Duration;
+
+    #[rstest]
+    #[case("x^3-6x^2+11x-6", "(x-1)(x-2)(x-3)")]
+    #[case("x^2+2x+1", "(x+1)^2")]
+    #[case("x^3-x", "x(x-1)(x+1)")]
+    #[case("2x^2-2", "2(x-1)(x+1)")]
+    #[case("6x^2+x-1", "(3x-1)(2x+1)")]
+    #[case("4x^2-4x+1", "(2x-1)^2")]
+    #[case("-x^2+2x-1", "-(x-1)^2")]
+    #[case("3x+6", "3(x+2)")]
+    #[case("x^4-1", "(x-1)(x+1)(x^2+1)")]
+    #[case("-2x^5-2x^3", "-2x^3(x^2+1)")]
+    #[case("x^4+4", "x^4+4")]
+    #[case("y^2-1", "(y-1)(y+1)")]
+    #[case("x-7", "x-7")]
+    #[case("x^2", "x^2")]
+    #[case("-5", "-5")]
+    #[case("0", "0")]
+    #[trace]
+    fn factored(#[case] expr: &str, #[case] expected: &str) {
+        assert_eq!(factor(expr), expected);
+    }
+
+    #[rstest]
+    #[case("(5m+3)^4")]
+    #[case("(2x-3)^3(x+1)(x^2+x+1)")]
+    #[case("12x(3x-4)^2(x^3-2)")]
+    #[case("-(7x+14)^2")]
+    #[trace]
+    fn inverse_of_expand(#[case] expr: &str) {
+        let expansion = expand(expr);
+        assert_eq!(expand(&factor(&expansion)), expansion);
+    }
+
+    #[rstest]
+    #[case("(3x-2)^34", "(3x-2)^34")]
+    #[case("(x+1)^60", "(x+1)^60")]
+    #[case("(6x-35)^20(x-1)", "(x-1)(6x-35)^20")]
+    #[case("x^2+100000000000000000000", "x^2+100000000000000000000")]
+    #[case("1000000000000000000000000x^2-1", "1000000000000000000000000x^2-1")]
+    #[case("x^2-10000000000000000", "(x-100000000)(x+100000000)")]
+    #[case("(4294967291x-4294967279)(x^2+1)", "(4294967291x-4294967279)(x^2+1)")]
+    #[case("18446744073709551557x^2-1", "18446744073709551557x^2-1")]
+    #[timeout(Duration::from_secs(1))]
+    #[trace]
+    fn high_multiplicities(#[case] expr: &str, #[case] expected: &str) {
+        assert_eq!(factor(&expand(expr)), expected);
+    }
+
+    #[test]
+    fn multiplicities() {
+        let p: Univariate<BigInt> = "(x-2)^3(2x+1)".parse().unwrap();
+        let factors = p.factor().factors;
+        let multiplicities = factors.iter().map(|(_, n)| *n).collect::<Vec<_>>();
+        assert_eq!(multiplicities, vec![1, 3]);
+    }
+
+    #[test]
+    fn divisors_of_integers() {
+        let expected = [1, 2, 3, 4, 6, 9, 12, 18, 36].map(BigInt::from);
+        assert_eq!(divisors(&BigInt::from(-36)), Some(expected.to_vec()));
+        assert_eq!(divisors(&BigInt::one()), Some(vec![BigInt::one()]));
+
+        let n = BigInt::from(u64::MAX);
+        assert_eq!(divisors(&n).map(|d| d.len()), Some(128));
+        assert_eq!(divisors(&(n + 1)), None);
+    }
+}
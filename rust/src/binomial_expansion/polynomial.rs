@@ -0,0 +1,630 @@
+//! Sparse multivariate polynomials with arbitrary-precision integer coefficients
+//!
+//! Polynomials are parsed from expressions such as `(x+2y-3)^5*(x-1)` by a recursive descent with
+//! the following grammar (where multiplication can be implicit, as in `2xy` or `(x+1)(x-1)`):
+//! ```text
+//! sum     := product (('+' | '-') product)*
+//! product := unary (('*')? power)*
+//! unary   := '-' unary | power
+//! power   := primary ('^' number)?
+//! primary := number | var | '(' sum ')'
+//! ```
+//! Variables are single letters.
+
+use num::{BigInt, One, Signed, Zero};
+use std::{
+    cmp::Ordering,
+    collections::{btree_map::Entry, BTreeMap},
+    fmt::{Display, Write},
+    ops::{Add, AddAssign, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+/// Upper bound on the size of a power parsed from an expression in bits, i.e. on the number of
+/// its terms times the bit size of its largest coefficient (see [Polynomial::checked_pow])
+pub const MAX_POW_BITS: u64 = 1 << 20;
+
+/// Product of variables raised to positive powers, e.g. `x^2y`, sorted by the variables
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Monomial(Vec<(char, u32)>);
+
+impl Monomial {
+    /// Returns the monomial consisting of just the variable `x`
+    #[inline]
+    pub fn var(x: char) -> Self {
+        Self::power(x, 1)
+    }
+
+    /// Returns the monomial `x^e` (which is the empty product for `e = 0`)
+    pub fn power(x: char, e: u32) -> Self {
+        if e == 0 {
+            Self::default()
+        } else {
+            Self(vec![(x, e)])
+        }
+    }
+
+    /// Returns the total degree of this monomial
+    #[inline]
+    pub fn degree(&self) -> u32 {
+        self.0.iter().map(|&(_, e)| e).sum()
+    }
+
+    /// Returns the exponent of variable `x` in this monomial
+    pub fn exponent(&self, x: char) -> u32 {
+        self.0
+            .iter()
+            .find_map(|&(y, e)| if x == y { Some(e) } else { None })
+            .unwrap_or(0)
+    }
+
+    /// Returns `true` iff this is the empty product (i.e. the monomial of constants)
+    #[inline]
+    pub fn is_one(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Mul for &Monomial {
+    type Output = Monomial;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        // Exponents of the same variable add up
+        let mut vars = BTreeMap::<char, u32>::new();
+        for &(x, e) in self.0.iter().chain(rhs.0.iter()) {
+            vars.entry(x).or_default().add_assign(e);
+        }
+        Monomial(vars.into_iter().collect())
+    }
+}
+
+/// Graded lexicographic order, i.e. monomials are ordered by their total degree and then
+/// lexicographically with `a > b > ... > z` (so `x^2 > xy > y^2 > x > y > 1`)
+impl Ord for Monomial {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.degree().cmp(&other.degree()).then_with(|| {
+            let (mut lhs, mut rhs) = (self.0.iter(), other.0.iter());
+            loop {
+                match (lhs.next(), rhs.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (Some(_), None) => return Ordering::Greater,
+                    (None, Some(_)) => return Ordering::Less,
+                    // The monomial with the (alphabetically) first variable is greater
+                    (Some((x, _)), Some((y, _))) if x != y => return y.cmp(x),
+                    (Some((_, i)), Some((_, j))) if i != j => return i.cmp(j),
+                    _ => continue,
+                }
+            }
+        })
+    }
+}
+
+impl PartialOrd for Monomial {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Monomial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for &(x, e) in &self.0 {
+            f.write_char(x)?;
+            if e > 1 {
+                write!(f, "^{e}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sparse multivariate polynomial with [BigInt] coefficients
+///
+/// Terms are kept with non-zero coefficients only and are displayed in the descending (graded
+/// lexicographic) order of their [Monomial]s, in the same format as [expand](super::expand)
+/// produces, e.g. `x^2+4xy+4y^2-6x-12y+9`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Polynomial(BTreeMap<Monomial, BigInt>);
+
+impl Polynomial {
+    /// Returns the polynomial consisting of just the variable `x`
+    #[inline]
+    pub fn var(x: char) -> Self {
+        Self(BTreeMap::from([(Monomial::var(x), BigInt::one())]))
+    }
+
+    /// Returns the constant polynomial `c`
+    pub fn constant(c: impl Into<BigInt>) -> Self {
+        let mut p = Self::default();
+        p.add_term(Monomial::default(), c.into());
+        p
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the total degree of this polynomial or `None` for the zero polynomial
+    #[inline]
+    pub fn degree(&self) -> Option<u32> {
+        self.0.keys().map(Monomial::degree).max()
+    }
+
+    /// Returns the coefficient of given monomial (which is zero if there's no such term)
+    pub fn coefficient(&self, monomial: &Monomial) -> BigInt {
+        self.0.get(monomial).cloned().unwrap_or_default()
+    }
+
+    /// Iterates over the terms of this polynomial in the descending order of their monomials
+    pub fn terms(&self) -> impl Iterator<Item = (&Monomial, &BigInt)> {
+        self.0.iter().rev()
+    }
+
+    /// Returns the set of variables of this polynomial
+    pub fn vars(&self) -> Vec<char> {
+        let mut vars = self
+            .0
+            .keys()
+            .flat_map(|m| m.0.iter().map(|&(x, _)| x))
+            .collect::<Vec<_>>();
+        vars.sort_unstable();
+        vars.dedup();
+        vars
+    }
+
+    /// Evaluates this polynomial where variable `x` has the value `values(x)`
+    pub fn eval(&self, values: impl Fn(char) -> BigInt) -> BigInt {
+        self.0
+            .iter()
+            .map(|(m, c)| {
+                m.0.iter()
+                    .fold(c.clone(), |y, &(x, e)| y * num::pow(values(x), e as usize))
+            })
+            .sum()
+    }
+
+    /// Like [pow](Self::pow), but returns `None` if the result might exceed [MAX_POW_BITS] (or if
+    /// its degree would overflow)
+    pub fn checked_pow(&self, n: u32) -> Option<Self> {
+        (self.pow_bits(n) <= MAX_POW_BITS).then(|| self.pow(n))
+    }
+
+    /// Returns an upper bound on the size of the `n`-th power of this polynomial in bits
+    fn pow_bits(&self, n: u32) -> u64 {
+        let n = u64::from(n);
+        let d = u64::from(self.degree().unwrap_or(0));
+        if n.saturating_mul(d) > u64::from(u32::MAX) {
+            return u64::MAX;
+        }
+
+        // Each term of `p^n` is a product of a multiset of `n` terms of `p` and also a monomial of
+        // degree at most `n * d` in the variables of `p`
+        let (t, v) = (self.0.len() as u64, self.vars().len() as u64);
+        let terms = match t {
+            0 | 1 => 1,
+            t => binomial(n + t - 1, t - 1).min(binomial(n * d + v, v)),
+        };
+
+        // Coefficients of `p^n` are bounded by `|p|^n`, where `|p|` is the sum of absolute values
+        // of the coefficients of `p`
+        let norm = self.0.values().map(BigInt::abs).sum::<BigInt>();
+        let bits = if norm.is_one() || norm.is_zero() {
+            1
+        } else {
+            norm.bits().saturating_mul(n)
+        };
+
+        terms.saturating_mul(bits)
+    }
+
+    /// Raises this polynomial to the `n`-th power by repeated squaring
+    pub fn pow(&self, mut n: u32) -> Self {
+        let mut base = self.clone();
+        let mut pow = Self::constant(1);
+        while n > 0 {
+            if n & 1 == 1 {
+                pow = &pow * &base;
+            }
+            n >>= 1;
+            if n > 0 {
+                base = &base * &base;
+            }
+        }
+        pow
+    }
+
+    fn add_term(&mut self, monomial: Monomial, c: BigInt) {
+        match self.0.entry(monomial) {
+            Entry::Vacant(entry) if !c.is_zero() => {
+                entry.insert(c);
+            }
+            Entry::Vacant(_) => {}
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() += c;
+                if entry.get().is_zero() {
+                    entry.remove();
+                }
+            }
+        }
+    }
+}
+
+/// Returns the binomial coefficient `C(n, k)`, which saturates at `u64::MAX`
+fn binomial(n: u64, k: u64) -> u64 {
+    let k = k.min(n - k);
+    let mut c: u128 = 1;
+    for i in 1..=k {
+        // Exact, since `C(n - k + i, i) = C(n - k + i - 1, i - 1) * (n - k + i) / i`
+        c = c * u128::from(n - k + i) / u128::from(i);
+        if c > u128::from(u64::MAX) {
+            return u64::MAX;
+        }
+    }
+    c as u64
+}
+
+impl Neg for Polynomial {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.into_iter().map(|(m, c)| (m, -c)).collect())
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut sum = self.clone();
+        for (m, c) in &rhs.0 {
+            sum.add_term(m.clone(), c.clone());
+        }
+        sum
+    }
+}
+
+impl Sub for &Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mut diff = self.clone();
+        for (m, c) in &rhs.0 {
+            diff.add_term(m.clone(), -c);
+        }
+        diff
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut product = Polynomial::default();
+        for (m1, c1) in &self.0 {
+            for (m2, c2) in &rhs.0 {
+                product.add_term(m1 * m2, c1 * c2);
+            }
+        }
+        product
+    }
+}
+
+macro_rules! impl_owned_op {
+    ($op:ident => $f:ident) => {
+        impl $op for Polynomial {
+            type Output = Self;
+
+            #[inline]
+            fn $f(self, rhs: Self) -> Self::Output {
+                (&self).$f(&rhs)
+            }
+        }
+    };
+}
+
+impl_owned_op!(Add => add);
+impl_owned_op!(Sub => sub);
+impl_owned_op!(Mul => mul);
+
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return f.write_char('0');
+        }
+
+        for (i, (m, c)) in self.terms().enumerate() {
+            write!(f, "{}", Term::new(c, m, i == 0))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Single term of a polynomial in the [expand](super::expand) format, i.e. the coefficient (with
+/// unit coefficients omitted) followed by the monomial, e.g. `-3x^2y`, and prefixed by `+` if it's
+/// positive and not the first term.
+pub(super) struct Term<'a, C> {
+    coef: &'a C,
+    monomial: &'a Monomial,
+    first: bool,
+}
+
+impl<'a, C> Term<'a, C> {
+    #[inline]
+    pub(super) fn new(coef: &'a C, monomial: &'a Monomial, first: bool) -> Self {
+        Self {
+            coef,
+            monomial,
+            first,
+        }
+    }
+}
+
+impl<C: Display + Signed> Display for Term<'_, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            coef: c,
+            monomial: m,
+            first,
+        } = self;
+
+        if !first && c.is_positive() {
+            f.write_char('+')?;
+        }
+
+        if m.is_one() {
+            write!(f, "{c}")
+        } else if c.is_one() {
+            write!(f, "{m}")
+        } else if c.is_negative() && c.abs().is_one() {
+            write!(f, "-{m}")
+        } else {
+            write!(f, "{c}{m}")
+        }
+    }
+}
+
+impl FromStr for Polynomial {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let p = parser.sum()?;
+        match parser.peek() {
+            None => Ok(p),
+            Some((token, at)) => Err(format!("Unexpected '{token}' at {at}")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(BigInt),
+    Var(char),
+    Op(char),
+    LeftParen,
+    RightParen,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Num(n) => write!(f, "{n}"),
+            Self::Var(x) | Self::Op(x) => f.write_char(*x),
+            Self::LeftParen => f.write_char('('),
+            Self::RightParen => f.write_char(')'),
+        }
+    }
+}
+
+/// Splits given string into [Token]s paired with their byte offsets
+fn tokenize(s: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((at, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '+' | '-' | '*' | '^' => Token::Op(c),
+            c if c.is_ascii_alphabetic() => Token::Var(c),
+            c if c.is_ascii_digit() => {
+                let mut end = at + 1;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !c.is_ascii_digit() {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                Token::Num(s[at..end].parse().expect("digits"))
+            }
+            c => return Err(format!("Unexpected character '{c}' at {at}")),
+        };
+        tokens.push((token, at));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    #[inline]
+    fn peek(&self) -> Option<&(Token, usize)> {
+        self.tokens.get(self.pos)
+    }
+
+    #[inline]
+    fn next(&mut self) -> Option<(Token, usize)> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consumes the next token if it's one of given operators
+    fn next_op(&mut self, ops: &[char]) -> Option<char> {
+        match self.peek() {
+            Some((Token::Op(op), _)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn sum(&mut self) -> Result<Polynomial, String> {
+        let mut lhs = self.product()?;
+        while let Some(op) = self.next_op(&['+', '-']) {
+            let rhs = self.product()?;
+            lhs = if op == '+' { lhs + rhs } else { lhs - rhs };
+        }
+        Ok(lhs)
+    }
+
+    fn product(&mut self) -> Result<Polynomial, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            let rhs = if self.next_op(&['*']).is_some() {
+                self.unary()?
+            } else if let Some((Token::Var(_) | Token::LeftParen, _)) = self.peek() {
+                // Implicit multiplication
+                self.power()?
+            } else {
+                return Ok(lhs);
+            };
+            lhs = lhs * rhs;
+        }
+    }
+
+    fn unary(&mut self) -> Result<Polynomial, String> {
+        if self.next_op(&['-']).is_some() {
+            return Ok(-self.unary()?);
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Polynomial, String> {
+        let base = self.primary()?;
+        if self.next_op(&['^']).is_none() {
+            return Ok(base);
+        }
+        match self.next() {
+            Some((Token::Num(n), at)) => u32::try_from(n)
+                .ok()
+                .and_then(|n| base.checked_pow(n))
+                .ok_or_else(|| format!("Exponent is too large at {at}")),
+            Some((token, at)) => Err(format!("Expected an exponent but got '{token}' at {at}")),
+            None => Err("Expected an exponent at the end of expression".to_string()),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Polynomial, String> {
+        match self.next() {
+            Some((Token::Num(n), _)) => Ok(Polynomial::constant(n)),
+            Some((Token::Var(x), _)) => Ok(Polynomial::var(x)),
+            Some((Token::LeftParen, at)) => {
+                let p = self.sum()?;
+                match self.next() {
+                    Some((Token::RightParen, _)) => Ok(p),
+                    _ => Err(format!("Unmatched '(' at {at}")),
+                }
+            }
+            Some((token, at)) => Err(format!("Unexpected '{token}' at {at}")),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::time::Duration;
+
+    #[rstest]
+    #[case("x+y", "x+y")]
+    #[case("y+x", "x+y")]
+    #[case("(x+y)^2", "x^2+2xy+y^2")]
+    #[case("(x+2y-3)^2", "x^2+4xy+4y^2-6x-12y+9")]
+    #[case("(x-1)(x+1)", "x^2-1")]
+    #[case("(x+1)^2-(x-1)^2", "4x")]
+    #[case("-(a-b)^3", "-a^3+3a^2b-3ab^2+b^3")]
+    #[case("2xy*3x", "6x^2y")]
+    #[case("x - x", "0")]
+    #[case("(x - 1)^0", "1")]
+    #[case("-2^2", "-4")]
+    #[case("(z+y+x)^2", "x^2+2xy+2xz+y^2+2yz+z^2")]
+    #[trace]
+    fn expansion(#[case] expr: &str, #[case] expected: &str) {
+        let p: Polynomial = expr.parse().unwrap();
+        assert_eq!(p.to_string(), expected);
+    }
+
+    #[test]
+    fn multinomial() {
+        let p: Polynomial = "(x+2y-3)^5*(x-1)".parse().unwrap();
+
+        assert_eq!(p.degree(), Some(6));
+        assert_eq!(p.vars(), vec!['x', 'y']);
+        assert_eq!(p.terms().count(), 27);
+        assert_eq!(p.coefficient(&Monomial::power('x', 6)), BigInt::one());
+        assert_eq!(p.coefficient(&Monomial::default()), BigInt::from(243));
+        assert_eq!(
+            p.coefficient(&Monomial(vec![('x', 1), ('y', 5)])),
+            BigInt::from(32)
+        );
+
+        // (2 + 10 - 3)^5 * (2 - 1)
+        let value = p.eval(|x| if x == 'x' { 2.into() } else { 5.into() });
+        assert_eq!(value, BigInt::from(59049));
+    }
+
+    #[rstest]
+    #[case("0", 1000000, true)]
+    #[case("-x", 1000000, true)]
+    #[case("x+1", 500, true)]
+    #[case("x+1", 3000, false)]
+    #[case("(x+y+1)", 50, true)]
+    #[case("(x+y+1)", 1000, false)]
+    #[case("x^2y-3xy+2", 50, true)]
+    #[case("x^2y-3xy+2", 1000, false)]
+    fn checked_powers(#[case] p: &str, #[case] n: u32, #[case] ok: bool) {
+        let p: Polynomial = p.parse().unwrap();
+        assert_eq!(p.checked_pow(n).is_some(), ok);
+    }
+
+    #[test]
+    fn large_coefficients() {
+        let p: Polynomial = "(x+1)^70".parse().unwrap();
+        let middle = Monomial(vec![('x', 35)]);
+        assert_eq!(
+            p.coefficient(&middle),
+            "112186277816662845432".parse::<BigInt>().unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("x+", "Unexpected end of expression")]
+    #[case("(x+1", "Unmatched '(' at 0")]
+    #[case("x^y", "Expected an exponent but got 'y' at 2")]
+    #[case("x^", "Expected an exponent at the end of expression")]
+    #[case("x/2", "Unexpected character '/' at 1")]
+    #[case("x 2", "Unexpected '2' at 2")]
+    #[case("x^99999999999", "Exponent is too large at 2")]
+    #[case("(x+y+1)^100000", "Exponent is too large at 8")]
+    #[case("(x+1)^60000", "Exponent is too large at 6")]
+    #[case("(x^70000)^70000", "Exponent is too large at 10")]
+    #[timeout(Duration::from_secs(1))]
+    #[trace]
+    fn parse_failures(#[case] expr: &str, #[case] expected: &str) {
+        assert_eq!(expr.parse::<Polynomial>(), Err(expected.to_string()));
+    }
+}
@@ -0,0 +1,441 @@
+//! Dense polynomials in a single variable over integers or rationals
+//!
+//! Ring operations, [Horner's](https://en.wikipedia.org/wiki/Horner%27s_method) evaluation and
+//! formal derivatives are available for any [Signed] coefficients, while long division, GCD and
+//! root bounds require a field, i.e. [BigRational] coefficients.
+
+use super::{polynomial::Term, Monomial, Polynomial};
+use num::{BigInt, BigRational, FromPrimitive, Integer, Signed, Zero};
+use std::{
+    fmt::{Display, Write},
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+/// Polynomial `a_0 + a_1 x + ... + a_n x^n` in variable `x` with coefficients of type `T`
+///
+/// Coefficients are stored in the ascending order of powers without trailing zeros, so the zero
+/// polynomial has no coefficients at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Univariate<T> {
+    var: char,
+    coefs: Vec<T>,
+}
+
+impl<T: Clone + Signed> Univariate<T> {
+    /// Creates a polynomial in `var` with given coefficients in the ascending order of powers
+    pub fn new(var: char, coefs: impl Into<Vec<T>>) -> Self {
+        let mut coefs = coefs.into();
+        while matches!(coefs.last(), Some(a) if a.is_zero()) {
+            coefs.pop();
+        }
+        Self { var, coefs }
+    }
+
+    #[inline]
+    pub fn zero(var: char) -> Self {
+        Self::new(var, [])
+    }
+
+    #[inline]
+    pub fn var(&self) -> char {
+        self.var
+    }
+
+    /// Returns coefficients in the ascending order of powers
+    #[inline]
+    pub fn coefficients(&self) -> &[T] {
+        &self.coefs
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.coefs.is_empty()
+    }
+
+    /// Returns the degree of this polynomial or `None` for the zero polynomial
+    #[inline]
+    pub fn degree(&self) -> Option<usize> {
+        self.coefs.len().checked_sub(1)
+    }
+
+    /// Returns the coefficient of the highest power (if it's not the zero polynomial)
+    #[inline]
+    pub fn leading(&self) -> Option<&T> {
+        self.coefs.last()
+    }
+
+    /// Evaluates this polynomial at `x` by Horner's method
+    pub fn eval(&self, x: &T) -> T {
+        self.coefs
+            .iter()
+            .rev()
+            .fold(T::zero(), |y, a| y * x.clone() + a.clone())
+    }
+
+    /// Maps each coefficient by given function
+    fn map<U: Clone + Signed>(&self, f: impl Fn(&T) -> U) -> Univariate<U> {
+        Univariate::new(self.var, self.coefs.iter().map(f).collect::<Vec<_>>())
+    }
+
+    /// Combines coefficients of the same powers of two polynomials (with the zero coefficient
+    /// standing in for the missing ones)
+    fn zip_with(&self, other: &Self, f: impl Fn(T, T) -> T) -> Self {
+        let n = self.coefs.len().max(other.coefs.len());
+        let coef = |p: &Self, i| p.coefs.get(i).cloned().unwrap_or_else(T::zero);
+        Self::new(
+            self.var,
+            (0..n)
+                .map(|i| f(coef(self, i), coef(other, i)))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl<T: Clone + Signed + FromPrimitive> Univariate<T> {
+    /// Returns the formal derivative of this polynomial
+    pub fn derivative(&self) -> Self {
+        let coefs = self
+            .coefs
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, a)| T::from_usize(i).expect("power fits the coefficient") * a.clone())
+            .collect::<Vec<_>>();
+        Self::new(self.var, coefs)
+    }
+}
+
+impl Univariate<BigInt> {
+    /// Returns the GCD of the coefficients, which is positive unless this is the zero polynomial
+    pub fn content(&self) -> BigInt {
+        self.coefs.iter().fold(BigInt::zero(), |gcd, a| gcd.gcd(a))
+    }
+
+    /// Divides this polynomial by its content so that the result has coprime coefficients and a
+    /// positive leading coefficient
+    pub fn primitive_part(&self) -> Self {
+        let mut content = self.content();
+        if matches!(self.leading(), Some(lead) if lead.is_negative()) {
+            content = -content;
+        }
+        self.map(|a| a / &content)
+    }
+
+    /// Returns the same polynomial over the rationals
+    pub fn to_rational(&self) -> Univariate<BigRational> {
+        self.map(|a| BigRational::from_integer(a.clone()))
+    }
+}
+
+impl Univariate<BigRational> {
+    /// Returns the primitive polynomial over the integers which is a rational multiple of this
+    /// one (see [Univariate::primitive_part])
+    pub fn to_integer(&self) -> Univariate<BigInt> {
+        let lcm = self
+            .coefs
+            .iter()
+            .fold(BigInt::from(1), |lcm, a| lcm.lcm(a.denom()));
+        self.map(|a| (a * &lcm).to_integer()).primitive_part()
+    }
+
+    /// Scales this polynomial so that its leading coefficient is `1`
+    pub fn monic(&self) -> Self {
+        match self.leading() {
+            Some(lead) => self.map(|a| a / lead),
+            None => self.clone(),
+        }
+    }
+
+    /// Long division returning the quotient and the remainder, whose degree is less than the
+    /// degree of the divisor
+    ///
+    /// # Panics
+    /// If the divisor is the zero polynomial.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let (Some(n), Some(lead)) = (divisor.degree(), divisor.leading()) else {
+            panic!("polynomial division by zero");
+        };
+
+        let mut rem = self.coefs.clone();
+        let mut quot = vec![BigRational::zero(); rem.len().saturating_sub(n)];
+
+        while rem.len() > n {
+            let shift = rem.len() - 1 - n;
+            let q = rem.last().expect("non-empty remainder") / lead;
+            for (r, d) in rem[shift..].iter_mut().zip(&divisor.coefs) {
+                *r -= &q * d;
+            }
+            rem.pop();
+            quot[shift] = q;
+        }
+
+        (Self::new(self.var, quot), Self::new(self.var, rem))
+    }
+
+    /// Returns the monic greatest common divisor by the Euclidean algorithm (or the zero
+    /// polynomial if both are zero)
+    pub fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let (_, r) = a.div_rem(&b);
+            a = std::mem::replace(&mut b, r);
+        }
+        a.monic()
+    }
+
+    /// Returns Cauchy's bound `1 + max |a_i / a_n|` on the absolute values of (complex) roots,
+    /// or `None` for the zero polynomial
+    pub fn root_bound(&self) -> Option<BigRational> {
+        let (lead, rest) = self.coefs.split_last()?;
+        let max = rest
+            .iter()
+            .map(|a| (a / lead).abs())
+            .max()
+            .unwrap_or_default();
+        Some(max + BigRational::from_integer(1.into()))
+    }
+}
+
+impl<T: Clone + Signed> Neg for Univariate<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self.map(|a| -a.clone())
+    }
+}
+
+impl<T: Clone + Signed> Add for &Univariate<T> {
+    type Output = Univariate<T>;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a + b)
+    }
+}
+
+impl<T: Clone + Signed> Sub for &Univariate<T> {
+    type Output = Univariate<T>;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a - b)
+    }
+}
+
+impl<T: Clone + Signed> Mul for &Univariate<T> {
+    type Output = Univariate<T>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        if self.is_zero() || rhs.is_zero() {
+            return Univariate::zero(self.var);
+        }
+        let mut coefs = vec![T::zero(); self.coefs.len() + rhs.coefs.len() - 1];
+        for (i, a) in self.coefs.iter().enumerate() {
+            for (j, b) in rhs.coefs.iter().enumerate() {
+                coefs[i + j] = coefs[i + j].clone() + a.clone() * b.clone();
+            }
+        }
+        Univariate::new(self.var, coefs)
+    }
+}
+
+macro_rules! impl_owned_op {
+    ($op:ident => $f:ident) => {
+        impl<T: Clone + Signed> $op for Univariate<T> {
+            type Output = Self;
+
+            #[inline]
+            fn $f(self, rhs: Self) -> Self::Output {
+                (&self).$f(&rhs)
+            }
+        }
+    };
+}
+
+impl_owned_op!(Add => add);
+impl_owned_op!(Sub => sub);
+impl_owned_op!(Mul => mul);
+
+/// Same format as [expand](super::expand), e.g. `x^2-2x+1` (or `1/2x-3/4` over the rationals)
+impl<T: Clone + Display + Signed> Display for Univariate<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return f.write_char('0');
+        }
+
+        let terms = self.coefs.iter().enumerate().rev();
+        for (i, (e, c)) in terms.filter(|(_, c)| !c.is_zero()).enumerate() {
+            let monomial = Monomial::power(self.var, e as u32);
+            write!(f, "{}", Term::new(c, &monomial, i == 0))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts a polynomial in at most one variable (which is `x` for constants)
+impl TryFrom<&Polynomial> for Univariate<BigInt> {
+    type Error = String;
+
+    fn try_from(p: &Polynomial) -> Result<Self, Self::Error> {
+        let var = match p.vars()[..] {
+            [] => 'x',
+            [x] => x,
+            ref vars => {
+                let vars = vars.iter().collect::<String>();
+                return Err(format!(
+                    "Expected a polynomial in one variable but got '{vars}'"
+                ));
+            }
+        };
+
+        let mut coefs = vec![BigInt::zero(); p.degree().map_or(0, |n| n as usize + 1)];
+        for (m, c) in p.terms() {
+            coefs[m.degree() as usize] = c.clone();
+        }
+        Ok(Self::new(var, coefs))
+    }
+}
+
+/// Parses (and expands) a polynomial expression in one variable, e.g. `(x-1)^2(x+2)`
+impl FromStr for Univariate<BigInt> {
+    type Err = String;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(&s.parse::<Polynomial>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn int(s: &str) -> Univariate<BigInt> {
+        s.parse().unwrap()
+    }
+
+    fn rat(s: &str) -> Univariate<BigRational> {
+        int(s).to_rational()
+    }
+
+    fn q(n: i64, d: i64) -> BigRational {
+        BigRational::new(n.into(), d.into())
+    }
+
+    #[rstest]
+    #[case("x^2+2x+1")]
+    #[case("625m^4-1500m^3+1350m^2-540m+81")]
+    #[case("-x^3+x")]
+    #[case("7")]
+    #[case("0")]
+    #[trace]
+    fn display_same_as_expand(#[case] expr: &str) {
+        assert_eq!(int(expr).to_string(), expr);
+    }
+
+    #[test]
+    fn display_rational() {
+        let p = Univariate::new('y', [q(-3, 4), q(1, 2), q(0, 1), q(-1, 1)]);
+        assert_eq!(p.to_string(), "-y^3+1/2y-3/4");
+    }
+
+    #[test]
+    fn arithmetic() {
+        let (p, q) = (int("x^2-1"), int("x+1"));
+        assert_eq!(&p + &q, int("x^2+x"));
+        assert_eq!(&p - &p, Univariate::zero('x'));
+        assert_eq!(&p * &q, int("(x-1)(x+1)^2"));
+        assert_eq!(-q, int("-x-1"));
+        assert_eq!(p.degree(), Some(2));
+        assert_eq!(Univariate::<BigInt>::zero('x').degree(), None);
+    }
+
+    #[rstest]
+    #[case("x^3-2x+5", 3, 26)]
+    #[case("(x-2)^5", 2, 0)]
+    #[case("4", -7, 4)]
+    #[trace]
+    fn horner(#[case] expr: &str, #[case] x: i64, #[case] y: i64) {
+        assert_eq!(int(expr).eval(&x.into()), y.into());
+    }
+
+    #[rstest]
+    #[case("x^3-2x+5", "3x^2-2")]
+    #[case("(x+1)^4", "4(x+1)^3")]
+    #[case("5", "0")]
+    #[trace]
+    fn derivative(#[case] expr: &str, #[case] expected: &str) {
+        assert_eq!(int(expr).derivative(), int(expected));
+    }
+
+    #[rstest]
+    #[case("x^3-6x^2+11x-6", "x-1", "x^2-5x+6", "0")]
+    #[case("x^3+2x+1", "x^2+1", "x", "x+1")]
+    #[case("x+1", "x^2", "0", "x+1")]
+    #[trace]
+    fn long_division(
+        #[case] dividend: &str,
+        #[case] divisor: &str,
+        #[case] quotient: &str,
+        #[case] remainder: &str,
+    ) {
+        let (q, r) = rat(dividend).div_rem(&rat(divisor));
+        assert_eq!((q, r), (rat(quotient), rat(remainder)));
+    }
+
+    #[test]
+    fn rational_division() {
+        let (quot, rem) = rat("x^2+1").div_rem(&rat("2x"));
+        assert_eq!(quot, Univariate::new('x', [BigRational::zero(), q(1, 2)]));
+        assert_eq!(rem, rat("1"));
+    }
+
+    #[test]
+    #[should_panic(expected = "polynomial division by zero")]
+    fn division_by_zero() {
+        rat("x").div_rem(&Univariate::zero('x'));
+    }
+
+    #[rstest]
+    #[case("(x-1)^2(x+2)", "(x-1)(x+3)", "x-1")]
+    #[case("2(x-1)(2x+1)", "(2x+1)^2", "x+1/2")]
+    #[case("x^2+1", "x-1", "1")]
+    #[case("0", "3x-6", "x-2")]
+    #[trace]
+    fn gcd(#[case] p: &str, #[case] q: &str, #[case] expected: &str) {
+        let gcd = rat(p).gcd(&rat(q));
+        assert_eq!(gcd.to_string(), expected);
+    }
+
+    #[test]
+    fn integer_conversions() {
+        let p = int("6x^2-4x+2");
+        assert_eq!(p.content(), BigInt::from(2));
+        assert_eq!(p.primitive_part(), int("3x^2-2x+1"));
+        assert_eq!((-p).primitive_part(), int("3x^2-2x+1"));
+
+        let monic = rat("(2x+1)(3x-1)").monic();
+        assert_eq!(monic.to_string(), "x^2+1/6x-1/6");
+        assert_eq!(monic.to_integer(), int("6x^2+x-1"));
+    }
+
+    #[rstest]
+    #[case("x^2-5x+6", q(7, 1))]
+    #[case("2x^3-x+1", q(3, 2))]
+    #[case("-4", q(1, 1))]
+    #[trace]
+    fn root_bound(#[case] expr: &str, #[case] bound: BigRational) {
+        assert_eq!(rat(expr).root_bound(), Some(bound));
+    }
+
+    #[test]
+    fn multivariate() {
+        assert_eq!(
+            "x+y".parse::<Univariate<BigInt>>(),
+            Err("Expected a polynomial in one variable but got 'xy'".to_string())
+        );
+    }
+}
@@ -1,33 +1,142 @@
 use crate::approx;
-use once_cell::sync::Lazy;
-use regex::Regex;
+use num::Integer;
 use std::{
     borrow::Borrow,
-    convert::{TryFrom, TryInto},
-    fmt::{Display, Write},
+    collections::BTreeSet,
+    fmt::Display,
+    num::NonZeroUsize,
     ops::{Add, BitXor, Deref, Div, Mul, Neg, Shr, Sub},
-    rc::Rc,
+    panic,
     str::FromStr,
+    sync::Arc,
+    thread,
 };
 
+mod arena;
+mod error;
+mod eval;
+mod infix;
+mod integration;
+mod latex;
+mod mathml;
+mod multivariate;
+mod prefix;
+mod rewrite;
+mod taylor;
+
+pub use arena::{Arena, Id, Node};
+pub use error::DiffError;
+pub use eval::eval;
+pub use infix::Infix;
+pub use integration::integrate;
+pub use latex::Latex;
+pub use mathml::MathMl;
+pub use multivariate::{gradient, hessian, jacobian, Matrix};
+
 pub fn diff(expr: &str) -> String {
-    expr.parse().and_then(Expr::into_diff).unwrap().to_string()
+    try_diff(expr).unwrap()
+}
+
+/// Like [diff], but instead of panicking on invalid input returns a [DiffError] which tells
+/// syntax errors, unknown function names and wrong number of operands (all located by their byte
+/// offsets) apart from expressions which can't be differentiated (e.g. unsupported forms such as
+/// `(^ -2 x)`).
+///
+/// The derivative is built as an expression tree, which can grow exponentially with the nesting
+/// depth of the expression, see [Arena::diff] for deep expressions.
+pub fn try_diff(expr: &str) -> Result<String, DiffError> {
+    prefix::parse(expr)?.into_diff().map(|df| df.to_string())
+}
+
+/// Differentiates given prefix expressions in parallel.
+///
+/// Unlike [diff], invalid expressions do not panic, instead the result for each expression is
+/// either its derivative or a [DiffError] (in the same order as the input expressions).
+pub fn diff_all(exprs: &[&str]) -> Vec<Result<String, DiffError>> {
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let chunk_size = Integer::div_ceil(&exprs.len(), &threads).max(1);
+
+    thread::scope(|s| {
+        let workers = exprs
+            .chunks(chunk_size)
+            .map(|chunk| {
+                s.spawn(move || chunk.iter().map(|expr| try_diff(expr)).collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    })
+}
+
+/// Returns the partial derivative of a prefix expression with respect to `var`, all the other
+/// variables are considered to be constants.
+pub fn diff_wrt(expr: &str, var: &str) -> String {
+    let expr: Expr = expr.parse().unwrap();
+    expr.derivative(var).unwrap().to_string()
+}
+
+/// Returns given prefix expression simplified by a set of rewriting rules (see
+/// [Expr::simplified]).
+pub fn simplify(expr: &str) -> String {
+    expr.parse::<Expr>().unwrap().simplified().to_string()
+}
+
+/// Like [diff], but the input expression is written in `from` notation and the derivative is
+/// returned in `to` notation (e.g. `diff_with("2*sin(x)", Infix, Infix) == "2 * cos(x)"`).
+pub fn diff_with(expr: &str, from: Notation, to: Notation) -> String {
+    let expr = from.parse(expr).unwrap();
+    to.render(&expr.into_diff().unwrap())
+}
+
+/// Textual representation of expressions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notation {
+    /// Prefix S-expressions such as `(* 2 (^ (sin x) 2))`
+    Prefix,
+    /// Conventional infix notation such as `2*sin(x)^2`
+    Infix,
+}
+
+impl Notation {
+    pub fn parse(self, expr: &str) -> Result<Expr, DiffError> {
+        match self {
+            Self::Prefix => prefix::parse(expr),
+            Self::Infix => infix::parse(expr),
+        }
+    }
+
+    pub fn render(self, expr: &Expr) -> String {
+        match self {
+            Self::Prefix => expr.to_string(),
+            Self::Infix => expr.infix().to_string(),
+        }
+    }
 }
 
 // Basic algebraic data structures
 //  - Function and binary operation enumerations
 //  - Expression representations
-//  - An `Rc` wrapper for expressions
-//  - A wrapper that helps to implement `TryFrom` for binary operations which might simplify to
-//  generic expressions
+//  - An `Arc` wrapper for expressions
 
-#[derive(Clone, Copy, Debug)]
-enum Func {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Func {
     Sin,
     Cos,
     Tan,
     Exp,
     Ln,
+    Sqrt,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Abs,
+    Sign,
 }
 
 impl Display for Func {
@@ -38,12 +147,21 @@ impl Display for Func {
             Func::Tan => "tan",
             Func::Exp => "exp",
             Func::Ln => "ln",
+            Func::Sqrt => "sqrt",
+            Func::Asin => "asin",
+            Func::Acos => "acos",
+            Func::Atan => "atan",
+            Func::Sinh => "sinh",
+            Func::Cosh => "cosh",
+            Func::Tanh => "tanh",
+            Func::Abs => "abs",
+            Func::Sign => "sign",
         })
     }
 }
 
 impl FromStr for Func {
-    type Err = String;
+    type Err = DiffError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -52,34 +170,49 @@ impl FromStr for Func {
             "tan" => Ok(Self::Tan),
             "exp" => Ok(Self::Exp),
             "ln" => Ok(Self::Ln),
-            _ => Err(format!("Failed to parse a function name from '{s}'")),
+            "sqrt" => Ok(Self::Sqrt),
+            "asin" => Ok(Self::Asin),
+            "acos" => Ok(Self::Acos),
+            "atan" => Ok(Self::Atan),
+            "sinh" => Ok(Self::Sinh),
+            "cosh" => Ok(Self::Cosh),
+            "tanh" => Ok(Self::Tanh),
+            "abs" => Ok(Self::Abs),
+            "sign" => Ok(Self::Sign),
+            _ => Err(DiffError::UnknownFunction {
+                name: s.to_owned(),
+                at: 0,
+            }),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum Op {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Op {
     Add,
     Sub,
     Mul,
     Div,
     Pow,
+    /// Logarithm `(log x b)` of `x` with base `b`
+    Log,
 }
 
 impl Display for Op {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_char(match self {
-            Self::Add => '+',
-            Self::Sub => '-',
-            Self::Mul => '*',
-            Self::Div => '/',
-            Self::Pow => '^',
+        f.write_str(match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Pow => "^",
+            Self::Log => "log",
         })
     }
 }
 
 impl FromStr for Op {
-    type Err = String;
+    type Err = DiffError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -88,15 +221,19 @@ impl FromStr for Op {
             "*" => Ok(Self::Mul),
             "/" => Ok(Self::Div),
             "^" => Ok(Self::Pow),
-            _ => Err(format!("Failed to parse a binary operator from '{s}'")),
+            "log" => Ok(Self::Log),
+            _ => Err(DiffError::syntax(
+                format!("Expected a binary operator but got '{s}'"),
+                0,
+            )),
         }
     }
 }
 
 #[derive(Clone, Debug)]
-struct FuncExpr {
+pub struct FuncExpr {
     f: Func,
-    arg: Rc<Expr>,
+    arg: Arc<Expr>,
 }
 
 trait Of {
@@ -110,7 +247,7 @@ trait Of {
 
 impl Of for Func {
     type Expr = FuncExpr;
-    type OutExpr = ExprRc;
+    type OutExpr = ExprArc;
 
     #[inline]
     fn of(self, expr: &Self::Expr) -> Self::OutExpr
@@ -126,14 +263,14 @@ impl Of for Func {
 }
 
 #[derive(Clone, Debug)]
-struct OpExpr {
-    lhs: Rc<Expr>,
+pub struct OpExpr {
+    lhs: Arc<Expr>,
     op: Op,
-    rhs: Rc<Expr>,
+    rhs: Arc<Expr>,
 }
 
 #[derive(Clone, Debug)]
-enum Expr {
+pub enum Expr {
     Const(f64),
     Var(String),
     Unary(FuncExpr),
@@ -141,122 +278,68 @@ enum Expr {
 }
 
 #[derive(Clone, Debug)]
-struct ExprRc(Rc<Expr>);
-
-struct SimplifiedBinary(Expr);
+struct ExprArc(Arc<Expr>);
 
 // Expression parsing implementations
 
-fn split_operands(args: &str) -> Option<(&str, &str)> {
-    if !args.starts_with('(') {
-        args.split_once(' ')
-    } else if !args.ends_with(')') {
-        args.rsplit_once(' ')
-    } else {
-        static ARGS_RE: Lazy<Regex> = Lazy::new(|| {
-            Regex::new(r"^(?P<lhs>\(.*\)) (?P<rhs>\(.*\))$").expect("ARGS_RE failed to compile")
-        });
-
-        ARGS_RE
-            .captures(args)
-            .and_then(|cap| match (cap.name("lhs"), cap.name("rhs")) {
-                (Some(lhs), Some(rhs)) => Some((lhs.as_str(), rhs.as_str())),
-                _ => None,
-            })
-    }
-}
-
-impl TryFrom<(&str, &str)> for SimplifiedBinary {
-    type Error = String;
+impl FromStr for Expr {
+    type Err = DiffError;
 
-    fn try_from(value: (&str, &str)) -> Result<Self, Self::Error> {
-        let (lhs, rhs) = split_operands(value.1)
-            .ok_or(format!("Failed to separate operands from '{}", value.1))?;
-        let op = value.0.parse()?;
-        let lhs = lhs.parse::<Expr>()?;
-        let rhs = rhs.parse::<Expr>()?;
-        // Note: Simplify here so that we don't have to deal with `Rc`s inside `OpExpr` later
-        let expr = (op, lhs, rhs).valid()?.simplify();
-        Ok(Self(expr))
+    /// Parses an expression in prefix notation, see [try_diff] for the possible errors
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        prefix::parse(s)
     }
 }
 
-impl TryFrom<(&str, &str)> for FuncExpr {
-    type Error = String;
+impl Expr {
+    /// Creates a validated and simplified binary expression, just like the parsers do
+    pub fn binary(op: Op, lhs: Self, rhs: Self) -> Result<Self, DiffError> {
+        let expr = (op, lhs, rhs).valid()?;
+        Ok(expr.simplify())
+    }
 
-    fn try_from(value: (&str, &str)) -> Result<Self, Self::Error> {
-        Ok(Self {
-            f: value.0.parse()?,
-            arg: value.1.parse().map(Rc::new)?,
+    /// Creates an application of function `f` to `arg`
+    pub fn unary(f: Func, arg: Self) -> Self {
+        Self::Unary(FuncExpr {
+            f,
+            arg: Arc::new(arg),
         })
     }
-}
 
-impl FromStr for Expr {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Strip parentheses
-        let expr = if s.starts_with('(') {
-            // Prefix expressions statring with opening '(' must end with ')'
-            if !s.ends_with(')') {
-                return Err(format!("'{}' does not have matching parentheses", s));
-            }
-
-            // Prefix expressions must contain at least one symbol between parentheses
-            if s.len() < 3 {
-                return Err(format!("'{}' is too short to be valid", s));
-            }
-
-            &s[1..s.len() - 1]
-        } else {
-            s
-        };
-
-        // Split expressions like `+ 1 2` or `sin x` to (operator, arguments)
-        let expr = if let Some(split) = expr.split_once(' ') {
-            split
-                .try_into()
-                .map(|SimplifiedBinary(expr)| expr)
-                .or_else(|e1| {
-                    split
-                        .try_into()
-                        .map(Self::Unary)
-                        .map_err(|e2| format!("{} & {}", e1, e2))
-                })?
-        } else {
-            let negated = expr.starts_with('-');
-
-            // Constant or a variable
-            let expr = expr
-                .trim_start_matches('-')
-                .parse()
-                .map_or_else(|_| Self::Var(expr.to_owned()), Self::Const);
-
-            // Apply unary '-'
-            match expr {
-                x @ Self::Var(_) if negated => (Op::Sub, 0.into(), x).into(),
-                Self::Const(c) if negated => (-c).into(),
-                expr => expr,
-            }
-        };
-
-        Ok(expr)
+    /// Parses a constant or a (possibly negated) variable
+    fn atom(s: &str) -> Self {
+        let negated = s.starts_with('-');
+
+        // Constant or a variable
+        let s = s.trim_start_matches('-');
+        let expr = s
+            .parse()
+            .map_or_else(|_| Self::Var(s.to_owned()), Self::Const);
+
+        // Apply unary '-'
+        match expr {
+            x @ Self::Var(_) if negated => (Op::Sub, 0.into(), x).into(),
+            Self::Const(c) if negated => (-c).into(),
+            expr => expr,
+        }
     }
 }
 
 // Differentiation implementations
 
+/// Differentiation with respect to given variable, where any other variable is considered to be a
+/// constant (i.e. a partial derivative)
 trait Diff {
     type OutExpr;
 
-    fn diff(&self) -> Result<Self::OutExpr, String>;
+    fn diff(&self, var: &str) -> Result<Self::OutExpr, DiffError>;
 }
 
 impl Diff for FuncExpr {
-    type OutExpr = ExprRc;
+    type OutExpr = ExprArc;
 
-    fn diff(&self) -> Result<Self::OutExpr, String> {
+    fn diff(&self, var: &str) -> Result<Self::OutExpr, DiffError> {
         use Expr::*;
         use Func::*;
 
@@ -270,58 +353,95 @@ impl Diff for FuncExpr {
             Tan => 1 / (Cos.of(self) ^ 2),
             Exp => Ok(self.into()),
             Ln => 1 / Self::OutExpr::from(self.arg.clone()),
+            Sqrt => 1 / (2.0 * Sqrt.of(self)),
+            Asin => 1 / Self::OutExpr::from((Sqrt, &(Self::OutExpr::from(1) - (self.arg() ^ 2)))),
+            Acos => -1 / Self::OutExpr::from((Sqrt, &(Self::OutExpr::from(1) - (self.arg() ^ 2)))),
+            Atan => 1 / (Self::OutExpr::from(1) + (self.arg() ^ 2)),
+            Sinh => Ok(Cosh.of(self)),
+            Cosh => Ok(Sinh.of(self)),
+            Tanh => 1 / (Cosh.of(self) ^ 2),
+            Abs => Ok(Sign.of(self)),
+            // Note: The derivative is not defined at 0, otherwise it's zero
+            Sign => Ok(0.into()),
         }?;
 
         // Apply the chain rule
-        df >> self.arg.clone().into()
+        df >> (self.arg.clone().into(), var)
+    }
+}
+
+impl FuncExpr {
+    #[inline]
+    fn arg(&self) -> ExprArc {
+        self.arg.clone().into()
     }
 }
 
 impl Diff for OpExpr {
-    type OutExpr = ExprRc;
+    type OutExpr = ExprArc;
 
-    fn diff(&self) -> Result<Self::OutExpr, String> {
-        use Expr::*;
+    fn diff(&self, var: &str) -> Result<Self::OutExpr, DiffError> {
         use Func::*;
         use Op::*;
 
-        let f: ExprRc = self.lhs.clone().into();
-        let g: ExprRc = self.rhs.clone().into();
+        let f: ExprArc = self.lhs.clone().into();
+        let g: ExprArc = self.rhs.clone().into();
 
         match self.op {
             Add => {
-                let df = f.diff()?;
-                let dg = g.diff()?;
+                let df = f.diff(var)?;
+                let dg = g.diff(var)?;
                 Ok(df + dg)
             }
             Sub => {
-                let df = f.diff()?;
-                let dg = g.diff()?;
+                let df = f.diff(var)?;
+                let dg = g.diff(var)?;
                 Ok(df - dg)
             }
             Mul => {
-                let df = f.diff()?;
-                let dg = g.diff()?;
+                let df = f.diff(var)?;
+                let dg = g.diff(var)?;
                 Ok((df * g) + (f * dg))
             }
             Div => {
-                let df = f.diff()?;
-                let dg = g.diff()?;
+                let df = f.diff(var)?;
+                let dg = g.diff(var)?;
                 let g2 = g.clone() ^ 2;
                 ((df * g) - (f * dg)) / g2
             }
-            Pow => {
-                let df = match (f.borrow(), g.borrow()) {
-                    (Const(_), _) => Self::OutExpr::from((Pow, f.0.clone(), g.0)) * (Ln, &f).into(),
-                    (_, Const(a)) => *a * (f.clone() ^ (a - 1.0)),
-                    (f, g) => {
-                        return Err(format!(
-                            "Can't diff '(^ {} {})', only forms supported are a^x and x^a",
-                            f, g
-                        ));
-                    }
-                };
-                df >> f
+            Pow => match (f.depends_on(var), g.depends_on(var)) {
+                // Power rule: d(f^a) = a * f^(a - 1) * df
+                (_, false) => {
+                    let df = g.clone() * (f.clone() ^ (g - 1.into()));
+                    df >> (f, var)
+                }
+                // Powers of a non-positive constant are not even real for most exponents
+                (false, true) if matches!(*f.0, Expr::Const(a) if a <= 0.0) => Err(
+                    DiffError::UnsupportedForm(format!("({Pow} {} {})", f.0, g.0)),
+                ),
+                // Exponential rule: d(a^g) = a^g * ln(a) * dg
+                (false, true) => {
+                    let df = Self::OutExpr::from((Pow, f.0.clone(), g.0.clone())) * (Ln, &f).into();
+                    df >> (g, var)
+                }
+                // General power rule: d(f^g) = d(exp(g ln(f))) = f^g * (dg * ln(f) + g * df / f)
+                (true, true) => {
+                    let df = f.diff(var)?;
+                    let dg = g.diff(var)?;
+                    let fg = Self::OutExpr::from((Pow, f.0.clone(), g.0.clone()));
+                    let ln_f = (Ln, &f).into();
+                    Ok(fg * ((dg * ln_f) + (g * (df / f)?)))
+                }
+            },
+            // d(log_b(f)) = d(ln(f) / ln(b))
+            Log if !g.depends_on(var) => {
+                let df = 1 / (f.clone() * (Ln, &g).into());
+                df? >> (f, var)
+            }
+            Log => {
+                let ln_f: Self::OutExpr = (Ln, &f).into();
+                let ln_g: Self::OutExpr = (Ln, &g).into();
+                (ln_f / ln_g)?.diff(var)
             }
         }
     }
@@ -330,40 +450,96 @@ impl Diff for OpExpr {
 impl Diff for Expr {
     type OutExpr = Self;
 
-    fn diff(&self) -> Result<Self::OutExpr, String> {
+    fn diff(&self, var: &str) -> Result<Self::OutExpr, DiffError> {
         match self {
             Self::Const(_) => Ok(0.into()),
-            Self::Var(_) => Ok(1.into()),
-            Self::Unary(f) => f.diff().map(Self::OutExpr::from),
-            Self::Binary(op) => op.diff().map(Self::OutExpr::from),
+            Self::Var(v) if v == var => Ok(1.into()),
+            Self::Var(_) => Ok(0.into()),
+            Self::Unary(f) => f.diff(var).map(Self::OutExpr::from),
+            Self::Binary(op) => op.diff(var).map(Self::OutExpr::from),
         }
     }
 }
 
-impl Diff for ExprRc {
+impl Diff for ExprArc {
     type OutExpr = Self;
 
-    fn diff(&self) -> Result<Self::OutExpr, String> {
-        self.0.diff().map(Self::OutExpr::from)
+    fn diff(&self, var: &str) -> Result<Self::OutExpr, DiffError> {
+        self.0.diff(var).map(Self::OutExpr::from)
     }
 }
 
+/// Differentiation of single-variable expressions (with respect to their only variable)
 trait IntoDiff {
     type OutExpr;
 
-    fn into_diff(self) -> Result<Self::OutExpr, String>;
+    fn into_diff(self) -> Result<Self::OutExpr, DiffError>;
 }
 
 impl IntoDiff for Expr {
     type OutExpr = Self;
 
-    fn into_diff(self) -> Result<Self::OutExpr, String> {
-        self.diff()
+    fn into_diff(self) -> Result<Self::OutExpr, DiffError> {
+        let vars = self.vars();
+        let mut vars = vars.iter();
+        match (vars.next(), vars.next()) {
+            (Some(var), None) => self.diff(var),
+            // Derivative of a constant is zero w.r.t. any variable
+            (None, _) => Ok(0.into()),
+            (Some(_), Some(_)) => Err(DiffError::AmbiguousVariable(self.to_string())),
+        }
+    }
+}
+
+impl Expr {
+    /// Returns the partial derivative of this expression with respect to `var`
+    #[inline]
+    pub fn derivative(&self, var: &str) -> Result<Self, DiffError> {
+        self.diff(var)
+    }
+
+    /// Returns the set of all variables in this expression
+    pub fn vars(&self) -> BTreeSet<&str> {
+        let mut vars = BTreeSet::new();
+        self.collect_vars(&mut vars);
+        vars
+    }
+
+    fn collect_vars<'a>(&'a self, vars: &mut BTreeSet<&'a str>) {
+        match self {
+            Self::Const(_) => {}
+            Self::Var(v) => {
+                vars.insert(v);
+            }
+            Self::Unary(FuncExpr { arg, .. }) => arg.collect_vars(vars),
+            Self::Binary(OpExpr { lhs, rhs, .. }) => {
+                lhs.collect_vars(vars);
+                rhs.collect_vars(vars);
+            }
+        }
+    }
+
+    /// Returns `true` iff `var` occurs in this expression
+    pub fn depends_on(&self, var: &str) -> bool {
+        match self {
+            Self::Const(_) => false,
+            Self::Var(v) => v == var,
+            Self::Unary(FuncExpr { arg, .. }) => arg.depends_on(var),
+            Self::Binary(OpExpr { lhs, rhs, .. }) => lhs.depends_on(var) || rhs.depends_on(var),
+        }
     }
 }
 
 // Display implementations
 
+impl Expr {
+    /// Returns a [Display] wrapper which renders this expression in infix notation
+    #[inline]
+    pub fn infix(&self) -> Infix<'_> {
+        Infix(self)
+    }
+}
+
 impl Display for Expr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -378,14 +554,14 @@ impl Display for Expr {
 // Expression validation
 
 trait Validated: Sized {
-    fn valid(self) -> Result<Self, String>;
+    fn valid(self) -> Result<Self, DiffError>;
 }
 
 impl<E> Validated for (Op, E, E)
 where
     E: Borrow<Expr> + Display,
 {
-    fn valid(self) -> Result<Self, String> {
+    fn valid(self) -> Result<Self, DiffError> {
         use Expr::*;
         use Op::*;
 
@@ -393,7 +569,7 @@ where
 
         match (op, lhs.borrow(), rhs.borrow()) {
             (Div, x, Const(c)) if approx!(c, 0) => {
-                Err(format!("Division by zero in '({} {} {})'", Div, x, c))
+                Err(DiffError::DivisionByZero(format!("({Div} {x} {c})")))
             }
             _ => Ok((op, lhs, rhs)),
         }
@@ -418,7 +594,7 @@ trait Simplify<F> {
 impl<E, F> Simplify<F> for (Op, E, E)
 where
     E: Borrow<Expr> + Display,
-    F: From<i8> + From<f64> + From<E> + From<Self> + From<(Op, Rc<Expr>, Rc<Expr>)>,
+    F: From<i8> + From<f64> + From<E> + From<Self> + From<(Op, Arc<Expr>, Arc<Expr>)>,
 {
     fn simplify(self) -> F {
         use Expr::*;
@@ -434,16 +610,17 @@ where
             (Mul, Const(x), Const(y)) => (x * y).into(),
             (Div, Const(x), Const(y)) => (x / y).into(),
             (Pow, Const(x), Const(y)) => x.powf(*y).into(),
+            (Log, Const(x), Const(y)) => x.log(*y).into(),
             (Add | Sub, _, Const(c)) if approx!(c, 0) => lhs.into(),
             (Add, Const(c), _) if approx!(c, 0) => rhs.into(),
             (Mul, Const(c), _) if approx!(c, 1) => rhs.into(),
             (Mul | Div | Pow, _, Const(c)) if approx!(c, 1) => lhs.into(),
             (Mul | Div, Const(c), _) | (Mul, _, Const(c)) if approx!(c, 0) => 0.into(),
             (Pow, _, Const(c)) if approx!(c, 0) => 1.into(),
-            // Unfortunately `if let` guards are not stable yet and one can't match on `Rc`
+            // Unfortunately `if let` guards are not stable yet and one can't match on `Arc`
             (Mul, Const(a), Binary(div)) | (Mul, Binary(div), Const(a)) if div.op == Div => {
                 if let Const(b) = *div.lhs {
-                    // Note: This clone is cheap as it only temporarily increments `Rc` counter
+                    // Note: This clone is cheap as it only temporarily increments `Arc` counter
                     (Div, Const(a * b).into(), div.rhs.clone()).into()
                 } else {
                     (op, lhs, rhs).into()
@@ -457,7 +634,7 @@ where
 // Expression algebra
 //  - Operations on expression refs additionally simplify (reduce) resulting expressions
 
-impl Neg for ExprRc {
+impl Neg for ExprArc {
     type Output = Self;
 
     fn neg(self) -> Self::Output {
@@ -487,22 +664,22 @@ macro_rules! impl_op {
     };
 }
 
-impl_op!(Add<ExprRc> => add);
-impl_op!(Sub<ExprRc> => sub);
-impl_op!(Mul<ExprRc> => mul);
-impl_op!(Pow<ExprRc> => bitxor@BitXor);
+impl_op!(Add<ExprArc> => add);
+impl_op!(Sub<ExprArc> => sub);
+impl_op!(Mul<ExprArc> => mul);
+impl_op!(Pow<ExprArc> => bitxor@BitXor);
 
-impl Mul<ExprRc> for f64 {
-    type Output = ExprRc;
+impl Mul<ExprArc> for f64 {
+    type Output = ExprArc;
 
     #[inline]
-    fn mul(self, rhs: ExprRc) -> Self::Output {
-        ExprRc::from(self) * rhs
+    fn mul(self, rhs: ExprArc) -> Self::Output {
+        ExprArc::from(self) * rhs
     }
 }
 
-impl Div for ExprRc {
-    type Output = Result<Self, String>;
+impl Div for ExprArc {
+    type Output = Result<Self, DiffError>;
 
     #[inline]
     fn div(self, rhs: Self) -> Self::Output {
@@ -510,12 +687,12 @@ impl Div for ExprRc {
     }
 }
 
-impl Div<ExprRc> for i8 {
-    type Output = Result<ExprRc, String>;
+impl Div<ExprArc> for i8 {
+    type Output = Result<ExprArc, DiffError>;
 
     #[inline]
-    fn div(self, rhs: ExprRc) -> Self::Output {
-        ExprRc::from(self) / rhs
+    fn div(self, rhs: ExprArc) -> Self::Output {
+        ExprArc::from(self) / rhs
     }
 }
 
@@ -523,7 +700,7 @@ impl Div<ExprRc> for i8 {
 #[macro_export]
 macro_rules! impl_bitxor_expr {
     ($t:ty) => {
-        impl BitXor<$t> for ExprRc {
+        impl BitXor<$t> for ExprArc {
             type Output = Self;
 
             #[inline]
@@ -537,23 +714,47 @@ macro_rules! impl_bitxor_expr {
 impl_bitxor_expr!(i8);
 impl_bitxor_expr!(f64);
 
-// Note that we interpret `self >> rhs` as the chain rule:
+// Note that we interpret `self >> (rhs, x)` as the chain rule:
 // `d(rhs)/dx * self` where `self` is assumed to be the derivative of an outer function
-impl Shr for ExprRc {
-    type Output = Result<Self, String>;
+impl Shr<(ExprArc, &str)> for ExprArc {
+    type Output = Result<Self, DiffError>;
 
     #[allow(clippy::suspicious_arithmetic_impl)]
-    fn shr(self, rhs: Self) -> Self::Output {
-        use Expr::*;
-        let expr = match (self.borrow(), rhs.borrow()) {
-            (Const(_) | Var(_), _) => self,
-            (_, Const(_) | Var(_)) => self,
-            _ => rhs.diff()? * self,
-        };
-        Ok(expr)
+    fn shr(self, rhs: (ExprArc, &str)) -> Self::Output {
+        let (rhs, var) = rhs;
+        Ok(rhs.diff(var)? * self)
+    }
+}
+
+impl Neg for Expr {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Self::Const(c) => Self::Const(-c),
+            expr => (-ExprArc::from(expr)).into(),
+        }
     }
 }
 
+// Only division is validated (see [Expr::binary]), so that the other operators are infallible
+macro_rules! impl_expr_op {
+    ($op:ident => $f:ident) => {
+        impl $op for Expr {
+            type Output = Self;
+
+            #[inline]
+            fn $f(self, rhs: Self) -> Self::Output {
+                (Op::$op, self, rhs).simplify()
+            }
+        }
+    };
+}
+
+impl_expr_op!(Add => add);
+impl_expr_op!(Sub => sub);
+impl_expr_op!(Mul => mul);
+
 // Data conversions
 
 impl From<f64> for Expr {
@@ -568,8 +769,8 @@ impl From<i8> for Expr {
     }
 }
 
-impl From<ExprRc> for Expr {
-    fn from(e: ExprRc) -> Self {
+impl From<ExprArc> for Expr {
+    fn from(e: ExprArc) -> Self {
         (*e).clone()
     }
 }
@@ -585,51 +786,51 @@ impl From<(Op, Expr, Expr)> for Expr {
     }
 }
 
-impl From<(Op, Rc<Expr>, Rc<Expr>)> for Expr {
-    fn from(e: (Op, Rc<Expr>, Rc<Expr>)) -> Self {
+impl From<(Op, Arc<Expr>, Arc<Expr>)> for Expr {
+    fn from(e: (Op, Arc<Expr>, Arc<Expr>)) -> Self {
         let (op, lhs, rhs) = e;
         Self::Binary(OpExpr { lhs, op, rhs })
     }
 }
 
-impl From<FuncExpr> for ExprRc {
+impl From<FuncExpr> for ExprArc {
     fn from(e: FuncExpr) -> Self {
         Expr::Unary(e).into()
     }
 }
 
-impl From<&FuncExpr> for ExprRc {
+impl From<&FuncExpr> for ExprArc {
     fn from(e: &FuncExpr) -> Self {
         e.clone().into()
     }
 }
 
-impl From<OpExpr> for ExprRc {
+impl From<OpExpr> for ExprArc {
     fn from(e: OpExpr) -> Self {
         Expr::Binary(e).into()
     }
 }
 
-impl From<ExprRc> for Rc<Expr> {
-    fn from(e: ExprRc) -> Self {
+impl From<ExprArc> for Arc<Expr> {
+    fn from(e: ExprArc) -> Self {
         e.0
     }
 }
 
-impl From<Rc<Expr>> for ExprRc {
-    fn from(e: Rc<Expr>) -> Self {
+impl From<Arc<Expr>> for ExprArc {
+    fn from(e: Arc<Expr>) -> Self {
         Self(e)
     }
 }
 
-impl From<&Rc<Expr>> for ExprRc {
-    fn from(e: &Rc<Expr>) -> Self {
+impl From<&Arc<Expr>> for ExprArc {
+    fn from(e: &Arc<Expr>) -> Self {
         Self((*e).clone())
     }
 }
 
-impl From<(Func, &ExprRc)> for ExprRc {
-    fn from(e: (Func, &ExprRc)) -> Self {
+impl From<(Func, &ExprArc)> for ExprArc {
+    fn from(e: (Func, &ExprArc)) -> Self {
         let (f, arg) = e;
         FuncExpr {
             f,
@@ -639,40 +840,40 @@ impl From<(Func, &ExprRc)> for ExprRc {
     }
 }
 
-impl From<(Op, Rc<Expr>, Rc<Expr>)> for ExprRc {
-    fn from(e: (Op, Rc<Expr>, Rc<Expr>)) -> Self {
+impl From<(Op, Arc<Expr>, Arc<Expr>)> for ExprArc {
+    fn from(e: (Op, Arc<Expr>, Arc<Expr>)) -> Self {
         let (op, lhs, rhs) = e;
         OpExpr { lhs, op, rhs }.into()
     }
 }
 
-impl From<Expr> for ExprRc {
+impl From<Expr> for ExprArc {
     fn from(e: Expr) -> Self {
         Self(e.into())
     }
 }
 
-impl From<f64> for ExprRc {
+impl From<f64> for ExprArc {
     fn from(v: f64) -> Self {
         let v: Expr = v.into();
         v.into()
     }
 }
 
-impl From<i8> for ExprRc {
+impl From<i8> for ExprArc {
     fn from(v: i8) -> Self {
         let v: Expr = v.into();
         v.into()
     }
 }
 
-impl Borrow<Expr> for ExprRc {
+impl Borrow<Expr> for ExprArc {
     fn borrow(&self) -> &Expr {
         self.0.as_ref()
     }
 }
 
-impl Deref for ExprRc {
+impl Deref for ExprArc {
     type Target = Expr;
 
     fn deref(&self) -> &Self::Target {
@@ -777,38 +978,204 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "'(+ x 1' does not have matching parentheses")]
+    #[should_panic(expected = "Unmatched '('")]
     fn missing_parenthesis() {
         diff("(exp (+ x 1)");
     }
 
     #[test]
-    #[should_panic(expected = "'()' is too short to be valid")]
+    #[should_panic(expected = "Empty expression")]
     fn empty_expression() {
         diff("(exp ())");
     }
 
     #[test]
-    #[should_panic(expected = "Failed to parse a function name from 'fn'")]
+    #[should_panic(expected = "UnknownFunction { name: \"fn\", at: 1 }")]
     fn unsupported_function() {
         diff("(fn x)");
     }
 
     #[test]
-    #[should_panic(expected = "Failed to parse a binary operator from '$'")]
+    #[should_panic(
+        expected = "Syntax { message: \"Expected a function or an operator but got '$'\", at: 1 }"
+    )]
     fn unsupported_operator() {
         diff("($ 1 2)");
     }
 
     #[test]
-    #[should_panic(expected = "Division by zero in '(/ (exp x) 0)'")]
+    #[should_panic(expected = "DivisionByZero(\"(/ (exp x) 0)\")")]
     fn division_by_zero() {
         diff("(/ (exp (* 1 x)) (- x x))");
     }
 
     #[test]
-    #[should_panic(expected = "Can't diff '(^ x x)', only forms supported are a^x and x^a")]
-    fn unsupported_power() {
-        diff("(^ x x)");
+    fn latex_derivative() {
+        let df = Notation::Infix
+            .parse("x^3 / 3 + cos(2*x)")
+            .unwrap()
+            .into_diff()
+            .unwrap();
+        assert_eq!(
+            df.latex().to_string(),
+            "\\frac{3 x^{2} \\cdot 3}{9} + 2 \\cdot \\left(-\\sin\\left(2 x\\right)\\right)"
+        );
+        assert_eq!(
+            df.simplified().latex().to_string(),
+            "x^{2} - 2 \\sin\\left(2 x\\right)"
+        );
+    }
+
+    #[test]
+    fn parallel_batch() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Expr>();
+        assert_send_sync::<Notation>();
+
+        let exprs = (0..100)
+            .map(|i| match i % 3 {
+                0 => format!("(* {i} x)"),
+                1 => format!("(^ x {i})"),
+                _ => format!("(/ x {})", i % 2),
+            })
+            .collect::<Vec<_>>();
+        let exprs = exprs.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let dfs = diff_all(&exprs);
+
+        assert_eq!(dfs.len(), exprs.len());
+        for (i, (expr, df)) in exprs.iter().zip(dfs).enumerate() {
+            match i % 3 {
+                2 if i % 2 == 0 => {
+                    assert_eq!(df, Err(DiffError::DivisionByZero(expr.to_string())));
+                }
+                _ => assert_eq!(df, Ok(diff(expr))),
+            }
+        }
+
+        assert!(diff_all(&[]).is_empty());
+        assert_eq!(
+            diff_all(&["(sin x)", "(", "(* x y)"]),
+            vec![
+                Ok("(cos x)".to_string()),
+                Err(DiffError::syntax("Unmatched '('", 0)),
+                Err(DiffError::AmbiguousVariable("(* x y)".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn general_power() {
+        assert_eq!(diff("(^ x x)"), "(* (^ x x) (+ (ln x) (* x (/ 1 x))))");
+        assert_eq!(
+            diff_with("x^sin(x)", Notation::Infix, Notation::Infix),
+            "x^sin(x) * (cos(x) * ln(x) + sin(x) * (1 / x))"
+        );
+    }
+
+    #[test]
+    fn elementary_functions() {
+        use Notation::*;
+
+        let df = |expr| diff_with(expr, Infix, Infix);
+
+        assert_eq!(df("sqrt(x)"), "1 / (2 * sqrt(x))");
+        assert_eq!(df("asin(2 * x)"), "2 / sqrt(1 - (2 * x)^2)");
+        assert_eq!(df("acos(x)"), "-1 / sqrt(1 - x^2)");
+        assert_eq!(df("atan(x)"), "1 / (1 + x^2)");
+        assert_eq!(df("sinh(x)"), "cosh(x)");
+        assert_eq!(df("cosh(x^2)"), "2 * x * sinh(x^2)");
+        assert_eq!(df("tanh(x)"), "1 / cosh(x)^2");
+        assert_eq!(df("abs(sin(x))"), "cos(x) * sign(sin(x))");
+        assert_eq!(df("sign(x)"), "0");
+        assert_eq!(df("log(x, 2)"), "1 / (x * ln(2))");
+        assert_eq!(df("log(3, x)"), "-(ln(3) * (1 / x)) / ln(x)^2");
+    }
+
+    #[test]
+    fn infix_notation() {
+        use Notation::*;
+
+        assert_eq!(diff_with("2*sin(x)", Infix, Infix), "2 * cos(x)");
+        assert_eq!(diff_with("cos x", Infix, Infix), "-sin(x)");
+        assert_eq!(diff_with("x^3", Infix, Infix), "3 * x^2");
+        assert_eq!(diff_with("2 / (1 + x)", Infix, Infix), "-2 / (1 + x)^2");
+        assert_eq!(diff_with("exp(2*x)", Infix, Prefix), "(* 2 (exp (* 2 x)))");
+        assert_eq!(
+            diff_with("(tan (* 2 x))", Prefix, Infix),
+            "2 / cos(2 * x)^2"
+        );
+    }
+
+    #[test]
+    fn partial_derivatives() {
+        assert_eq!(diff_wrt("(* x y)", "x"), "y");
+        assert_eq!(diff_wrt("(* x y)", "y"), "x");
+        assert_eq!(diff_wrt("(* x y)", "z"), "0");
+        assert_eq!(diff_wrt("(^ x y)", "x"), "(* y (^ x (- y 1)))");
+        assert_eq!(diff_wrt("(^ y x)", "x"), "(* (^ y x) (ln y))");
+        assert_eq!(diff_wrt("(sin (* x y))", "x"), "(* y (cos (* x y)))");
+        assert_eq!(diff_wrt("(+ (^ x 2) (exp y))", "y"), "(exp y)");
+    }
+
+    #[test]
+    fn exponential_chain_rule() {
+        assert_eq!(diff("(^ 2 (* 3 x))"), "(* 3 (* (^ 2 (* 3 x)) (ln 2)))");
+    }
+
+    #[test]
+    fn typed_errors() {
+        assert_eq!(try_diff("(^ x 3)"), Ok("(* 3 (^ x 2))".to_string()));
+        assert_eq!(
+            try_diff("(fn x)"),
+            Err(DiffError::UnknownFunction {
+                name: "fn".to_string(),
+                at: 1
+            })
+        );
+        assert_eq!(
+            try_diff("(exp (+ x 1)").unwrap_err().to_string(),
+            "Unmatched '(' at 0"
+        );
+        assert_eq!(
+            try_diff("(log x y z)").unwrap_err().to_string(),
+            "'log' at 1 expects 2 operand(s), got 3"
+        );
+        assert_eq!(
+            try_diff("(* x y)"),
+            Err(DiffError::AmbiguousVariable("(* x y)".to_string()))
+        );
+        assert_eq!(
+            try_diff("(/ x (- x x))"),
+            Err(DiffError::DivisionByZero("(/ x 0)".to_string()))
+        );
+        assert_eq!(
+            try_diff("($ 1 x)"),
+            Err(DiffError::syntax(
+                "Expected a function or an operator but got '$'",
+                1
+            ))
+        );
+        assert_eq!(
+            try_diff("(^ -2 x)"),
+            Err(DiffError::UnsupportedForm("(^ -2 x)".to_string()))
+        );
+        assert_eq!(
+            "fn".parse::<Func>(),
+            Err(DiffError::UnknownFunction {
+                name: "fn".to_string(),
+                at: 0
+            })
+        );
+        assert_eq!(
+            "$".parse::<Op>().unwrap_err().to_string(),
+            "Expected a binary operator but got '$' at 0"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "AmbiguousVariable(\"(* x y)\")")]
+    fn multiple_variables() {
+        diff("(* x y)");
     }
 }
@@ -0,0 +1,583 @@
+//! Hash-consed expression DAG
+//!
+//! An [Arena] interns each distinct (sub-)expression exactly once, so that structurally equal
+//! subtrees are shared and identified by the same [Id]. Consequently, equality of expressions
+//! interned in the same arena is just an `O(1)` comparison of their ids.
+//!
+//! Derivatives are computed directly on the DAG and memoized for each node, so repeated
+//! applications of the product and chain rules re-use already built sub-derivatives instead of
+//! copying them. This keeps the size of (higher-order) derivatives of deep expressions polynomial,
+//! whereas the size of the equivalent [Expr] trees can grow exponentially.
+//!
+//! Nodes are locally simplified on construction (constant folding, neutral elements, `x - x = 0`,
+//! `x / x = 1`) and operands of commutative operators are ordered by their ids, which further
+//! increases sharing. Simplifications which would drop an operand containing a division by zero
+//! (such as `0 * (x / 0) = 0`) are skipped, so that the division can still be reported.
+//!
+//! Note that only [Arena::diff] works on the DAG, [diff](super::diff), [try_diff](super::try_diff)
+//! and [Expr::derivative] still differentiate expression trees.
+
+use super::{DiffError, Expr, Func, FuncExpr, Op, OpExpr};
+use crate::approx;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// Handle of an expression interned in an [Arena]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id(u32);
+
+/// Single node of the expression DAG, whose operands are referenced by their [Id]s
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Node {
+    /// Constant represented by the bits of a (normalized) `f64`, see [Node::constant]
+    Const(u64),
+    Var(String),
+    Unary(Func, Id),
+    Binary(Op, Id, Id),
+}
+
+impl Node {
+    /// Returns the value of a [Node::Const] (or `None` for any other node)
+    #[inline]
+    pub fn constant(&self) -> Option<f64> {
+        match self {
+            Self::Const(bits) => Some(f64::from_bits(*bits)),
+            _ => None,
+        }
+    }
+}
+
+/// Interning arena of expression [Node]s
+#[derive(Debug, Default)]
+pub struct Arena {
+    nodes: Vec<Node>,
+    ids: HashMap<Node, Id>,
+    /// Whether each node (indexed by its id) contains a division by a zero constant
+    zero_divisions: Vec<bool>,
+    /// Memoized derivatives indexed by `(expression, variable)`
+    derivatives: HashMap<(Id, Id), Id>,
+}
+
+impl Arena {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct nodes in this arena
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the node identified by given id
+    #[inline]
+    pub fn get(&self, id: Id) -> &Node {
+        &self.nodes[id.0 as usize]
+    }
+
+    /// Returns the id of given node, inserting it into this arena if it's not already present
+    fn insert(&mut self, node: Node) -> Id {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = Id(u32::try_from(self.nodes.len()).expect("arena capacity exceeded"));
+        let zero_division = match node {
+            Node::Const(_) | Node::Var(_) => false,
+            Node::Unary(_, arg) => self.divides_by_zero(arg),
+            Node::Binary(op, lhs, rhs) => {
+                (op == Op::Div && self.get(rhs).constant() == Some(0.0))
+                    || self.divides_by_zero(lhs)
+                    || self.divides_by_zero(rhs)
+            }
+        };
+        self.zero_divisions.push(zero_division);
+        self.nodes.push(node.clone());
+        self.ids.insert(node, id);
+        id
+    }
+
+    pub fn constant(&mut self, c: f64) -> Id {
+        // Normalize `-0.0` and NaNs so that equal values have equal bits
+        let c = if c == 0.0 {
+            0.0
+        } else if c.is_nan() {
+            f64::NAN
+        } else {
+            c
+        };
+        self.insert(Node::Const(c.to_bits()))
+    }
+
+    pub fn var(&mut self, name: &str) -> Id {
+        self.insert(Node::Var(name.to_owned()))
+    }
+
+    pub fn unary(&mut self, f: Func, arg: Id) -> Id {
+        self.insert(Node::Unary(f, arg))
+    }
+
+    /// Returns the id of a locally simplified binary expression `(op lhs rhs)`
+    pub fn binary(&mut self, op: Op, lhs: Id, rhs: Id) -> Id {
+        use Op::*;
+
+        let (x, y) = (self.get(lhs).constant(), self.get(rhs).constant());
+        // Operands which can't be dropped by a simplification
+        let kept = self.divides_by_zero(lhs) || self.divides_by_zero(rhs);
+
+        match (op, x, y) {
+            (Add, Some(x), Some(y)) => self.constant(x + y),
+            (Sub, Some(x), Some(y)) => self.constant(x - y),
+            (Mul, Some(x), Some(y)) => self.constant(x * y),
+            (Div, Some(x), Some(y)) if y != 0.0 => self.constant(x / y),
+            (Pow, Some(x), Some(y)) => self.constant(x.powf(y)),
+            (Log, Some(x), Some(y)) => self.constant(x.log(y)),
+            (Add, Some(z), _) if approx!(z, 0) => rhs,
+            (Add | Sub, _, Some(z)) if approx!(z, 0) => lhs,
+            (Mul, Some(z), _) | (Mul, _, Some(z)) if approx!(z, 0) && !kept => self.constant(0.0),
+            (Mul, Some(one), _) if approx!(one, 1) => rhs,
+            (Mul | Div | Pow, _, Some(one)) if approx!(one, 1) => lhs,
+            // Division by zero is kept so that it can be reported, see [Arena::diff]
+            (Div, Some(z), _) if approx!(z, 0) && y != Some(0.0) && !kept => self.constant(0.0),
+            (Pow, _, Some(z)) if approx!(z, 0) && !kept => self.constant(1.0),
+            (Sub, _, _) if lhs == rhs && !kept => self.constant(0.0),
+            (Div, _, _) if lhs == rhs && y != Some(0.0) && !kept => self.constant(1.0),
+            // Canonical order of operands of commutative operators
+            (Add | Mul, _, _) if rhs < lhs => self.insert(Node::Binary(op, rhs, lhs)),
+            _ => self.insert(Node::Binary(op, lhs, rhs)),
+        }
+    }
+
+    /// Interns given expression (and recursively all of its sub-expressions)
+    pub fn intern(&mut self, expr: &Expr) -> Id {
+        match expr {
+            Expr::Const(c) => self.constant(*c),
+            Expr::Var(v) => self.var(v),
+            Expr::Unary(FuncExpr { f, arg }) => {
+                let arg = self.intern(arg);
+                self.unary(*f, arg)
+            }
+            Expr::Binary(OpExpr { lhs, op, rhs }) => {
+                let lhs = self.intern(lhs);
+                let rhs = self.intern(rhs);
+                self.binary(*op, lhs, rhs)
+            }
+        }
+    }
+
+    /// Converts the DAG rooted at `id` back to an [Expr], shared nodes are shared via `Arc`s
+    pub fn to_expr(&self, id: Id) -> Expr {
+        fn build(arena: &Arena, id: Id, built: &mut HashMap<Id, Arc<Expr>>) -> Arc<Expr> {
+            if let Some(expr) = built.get(&id) {
+                return expr.clone();
+            }
+            let expr = Arc::new(match arena.get(id) {
+                Node::Const(bits) => Expr::Const(f64::from_bits(*bits)),
+                Node::Var(v) => Expr::Var(v.clone()),
+                Node::Unary(f, arg) => Expr::Unary(FuncExpr {
+                    f: *f,
+                    arg: build(arena, *arg, built),
+                }),
+                Node::Binary(op, lhs, rhs) => Expr::Binary(OpExpr {
+                    lhs: build(arena, *lhs, built),
+                    op: *op,
+                    rhs: build(arena, *rhs, built),
+                }),
+            });
+            built.insert(id, expr.clone());
+            expr
+        }
+
+        build(self, id, &mut HashMap::new()).as_ref().clone()
+    }
+
+    /// Returns the number of distinct nodes reachable from `id`
+    pub fn dag_size(&self, id: Id) -> usize {
+        let mut visited = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if visited.insert(id) {
+                match self.get(id) {
+                    Node::Const(_) | Node::Var(_) => {}
+                    Node::Unary(_, arg) => stack.push(*arg),
+                    Node::Binary(_, lhs, rhs) => stack.extend([*lhs, *rhs]),
+                }
+            }
+        }
+        visited.len()
+    }
+
+    /// Returns the number of nodes of the expression tree rooted at `id` (i.e. without sharing)
+    pub fn tree_size(&self, id: Id) -> u128 {
+        fn size(arena: &Arena, id: Id, sizes: &mut HashMap<Id, u128>) -> u128 {
+            if let Some(&n) = sizes.get(&id) {
+                return n;
+            }
+            let n = match arena.get(id) {
+                Node::Const(_) | Node::Var(_) => 1,
+                Node::Unary(_, arg) => 1 + size(arena, *arg, sizes),
+                Node::Binary(_, lhs, rhs) => {
+                    let (l, r) = (size(arena, *lhs, sizes), size(arena, *rhs, sizes));
+                    l.saturating_add(r).saturating_add(1)
+                }
+            };
+            sizes.insert(id, n);
+            n
+        }
+
+        size(self, id, &mut HashMap::new())
+    }
+
+    /// Returns `true` iff variable `var` occurs in the expression `id`
+    pub fn depends_on(&self, id: Id, var: Id) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if id == var {
+                return true;
+            }
+            if visited.insert(id) {
+                match self.get(id) {
+                    Node::Const(_) | Node::Var(_) => {}
+                    Node::Unary(_, arg) => stack.push(*arg),
+                    Node::Binary(_, lhs, rhs) => stack.extend([*lhs, *rhs]),
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns `true` iff the expression `id` contains a division by a zero constant
+    #[inline]
+    fn divides_by_zero(&self, id: Id) -> bool {
+        self.zero_divisions[id.0 as usize]
+    }
+
+    /// Returns the first division by a zero constant in the expression `id` (if there is any)
+    fn zero_division(&self, id: Id) -> Option<Id> {
+        if !self.divides_by_zero(id) {
+            return None;
+        }
+        let mut visited = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if visited.insert(id) {
+                match self.get(id) {
+                    Node::Const(_) | Node::Var(_) => {}
+                    Node::Unary(_, arg) => stack.push(*arg),
+                    Node::Binary(Op::Div, _, den) if self.get(*den).constant() == Some(0.0) => {
+                        return Some(id)
+                    }
+                    Node::Binary(_, lhs, rhs) => stack.extend([*lhs, *rhs]),
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the (memoized) partial derivative of `id` with respect to variable `var`
+    ///
+    /// Fails if either the expression or its derivative contains a division by zero, or if the
+    /// expression contains a power of a non-positive constant to an exponent depending on `var`.
+    pub fn diff(&mut self, id: Id, var: &str) -> Result<Id, DiffError> {
+        let var = self.var(var);
+        self.check_division(id)?;
+        let df = self.diff_wrt(id, var)?;
+        self.check_division(df)
+    }
+
+    fn check_division(&self, id: Id) -> Result<Id, DiffError> {
+        match self.zero_division(id) {
+            Some(div) => Err(DiffError::DivisionByZero(self.to_expr(div).to_string())),
+            None => Ok(id),
+        }
+    }
+
+    fn diff_wrt(&mut self, id: Id, x: Id) -> Result<Id, DiffError> {
+        use Func::*;
+        use Op::*;
+
+        if let Some(&df) = self.derivatives.get(&(id, x)) {
+            return Ok(df);
+        }
+
+        let df = match self.get(id).clone() {
+            Node::Const(_) => self.constant(0.0),
+            Node::Var(_) if id == x => self.constant(1.0),
+            Node::Var(_) => self.constant(0.0),
+            Node::Unary(f, u) => {
+                let du = self.diff_wrt(u, x)?;
+                let one = self.constant(1.0);
+                // Derivative of the outer function `f` at `u`
+                let df = match f {
+                    Sin => self.unary(Cos, u),
+                    Cos => {
+                        let sin = self.unary(Sin, u);
+                        self.neg(sin)
+                    }
+                    Tan => {
+                        let cos = self.unary(Cos, u);
+                        let cos2 = self.square(cos);
+                        self.binary(Div, one, cos2)
+                    }
+                    Exp => id,
+                    Ln => self.binary(Div, one, u),
+                    Sqrt => {
+                        let two = self.constant(2.0);
+                        let den = self.binary(Mul, two, id);
+                        self.binary(Div, one, den)
+                    }
+                    Asin | Acos => {
+                        let u2 = self.square(u);
+                        let d = self.binary(Sub, one, u2);
+                        let sqrt = self.unary(Sqrt, d);
+                        let num = if let Asin = f {
+                            one
+                        } else {
+                            self.constant(-1.0)
+                        };
+                        self.binary(Div, num, sqrt)
+                    }
+                    Atan => {
+                        let u2 = self.square(u);
+                        let den = self.binary(Add, one, u2);
+                        self.binary(Div, one, den)
+                    }
+                    Sinh => self.unary(Cosh, u),
+                    Cosh => self.unary(Sinh, u),
+                    Tanh => {
+                        let cosh = self.unary(Cosh, u);
+                        let cosh2 = self.square(cosh);
+                        self.binary(Div, one, cosh2)
+                    }
+                    Abs => self.unary(Sign, u),
+                    Sign => self.constant(0.0),
+                };
+                self.binary(Mul, du, df)
+            }
+            Node::Binary(op, f, g) => {
+                let df = self.diff_wrt(f, x)?;
+                let dg = self.diff_wrt(g, x)?;
+                match op {
+                    Add => self.binary(Add, df, dg),
+                    Sub => self.binary(Sub, df, dg),
+                    Mul => {
+                        let l = self.binary(Mul, df, g);
+                        let r = self.binary(Mul, f, dg);
+                        self.binary(Add, l, r)
+                    }
+                    Div => {
+                        let l = self.binary(Mul, df, g);
+                        let r = self.binary(Mul, f, dg);
+                        let num = self.binary(Sub, l, r);
+                        let den = self.square(g);
+                        self.binary(Div, num, den)
+                    }
+                    // Power rule: d(f^a) = a * f^(a - 1) * df
+                    Pow if dg == self.constant(0.0) => {
+                        let one = self.constant(1.0);
+                        let exp = self.binary(Sub, g, one);
+                        let pow = self.binary(Pow, f, exp);
+                        let l = self.binary(Mul, g, pow);
+                        self.binary(Mul, l, df)
+                    }
+                    // Powers of a non-positive constant are not even real for most exponents
+                    Pow if matches!(self.get(f).constant(), Some(a) if a <= 0.0) => {
+                        return Err(DiffError::UnsupportedForm(self.to_expr(id).to_string()));
+                    }
+                    // d(f^g) = f^g * (dg * ln(f) + g * df / f)
+                    Pow => {
+                        let ln_f = self.unary(Ln, f);
+                        let l = self.binary(Mul, dg, ln_f);
+                        let df_f = self.binary(Div, df, f);
+                        let r = self.binary(Mul, g, df_f);
+                        let sum = self.binary(Add, l, r);
+                        self.binary(Mul, id, sum)
+                    }
+                    // d(log_g(f)) = d(ln(f) / ln(g))
+                    Log => {
+                        let ln_f = self.unary(Ln, f);
+                        let ln_g = self.unary(Ln, g);
+                        let quotient = self.binary(Div, ln_f, ln_g);
+                        self.diff_wrt(quotient, x)?
+                    }
+                }
+            }
+        };
+
+        self.derivatives.insert((id, x), df);
+        Ok(df)
+    }
+
+    #[inline]
+    fn neg(&mut self, id: Id) -> Id {
+        let minus_one = self.constant(-1.0);
+        self.binary(Op::Mul, minus_one, id)
+    }
+
+    #[inline]
+    fn square(&mut self, id: Id) -> Id {
+        let two = self.constant(2.0);
+        self.binary(Op::Pow, id, two)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::differentiation::infix::parsed;
+    use rstest::rstest;
+
+    #[test]
+    fn interning() {
+        let mut arena = Arena::new();
+
+        let a = arena.intern(&parsed("sin(x * y) + sin(y * x)"));
+        let b = arena.intern(&parsed("sin(x*y)"));
+        let c = arena.intern(&parsed("2 * sin(x * y)"));
+
+        // x, y, x*y, sin(x*y), sin(x*y) + sin(x*y), 2 and 2*sin(x*y)
+        assert_eq!(arena.len(), 7);
+        assert_eq!(arena.dag_size(a), 5);
+        assert_eq!(arena.tree_size(a), 9);
+        assert_ne!(a, b);
+        assert_eq!(arena.get(a), &Node::Binary(Op::Add, b, b));
+        assert_eq!(arena.intern(&parsed("sin(x * y) * 2")), c);
+        assert_eq!(
+            arena.to_expr(a).to_string(),
+            "(+ (sin (* x y)) (sin (* x y)))"
+        );
+    }
+
+    #[test]
+    fn local_simplification() {
+        let mut arena = Arena::new();
+
+        let zero = arena.constant(0.0);
+        let minus_zero = arena.constant(-0.0);
+        assert_eq!(zero, minus_zero);
+
+        let x = arena.intern(&parsed("x"));
+        assert_eq!(arena.intern(&parsed("(x + 0) * 1 / 1")), x);
+
+        let e = arena.intern(&parsed("exp(x) - exp(x)"));
+        assert_eq!(e, zero);
+    }
+
+    #[rstest]
+    #[case("x^3 * ln(x) + exp(2 * x) / x")]
+    #[case("x^x + asin(x / 3) - acos(x / 4) + sqrt(x + 1)")]
+    #[case("log(x, 2) * atan(x) + tanh(x) * abs(x - 2) + cosh(x) / sinh(x)")]
+    #[case("tan(x) * cos(x)^y")]
+    fn derivatives(#[case] expr: &str) {
+        let f = parsed(expr);
+        let mut arena = Arena::new();
+
+        let id = arena.intern(&f);
+        let df = arena.diff(id, "x").unwrap();
+        let df = arena.to_expr(df);
+
+        let expected = f.derivative("x").unwrap();
+        for x in [0.5, 1.0, 2.5] {
+            let env = [("x", x), ("y", 3.0)];
+            let (expected, actual) = (expected.eval(&env).unwrap(), df.eval(&env).unwrap());
+            assert!(
+                (actual - expected).abs() < 1e-9 * expected.abs().max(1.0),
+                "{actual} != {expected}"
+            );
+        }
+    }
+
+    #[rstest]
+    #[case("x^2", "(* x 2)")]
+    #[case("x^3", "(* 3 (^ x 2))")]
+    #[case("sin(x)^y", "(* (cos x) (* y (^ (sin x) (- y 1))))")]
+    #[trace]
+    fn power_rule(#[case] expr: &str, #[case] expected: &str) {
+        let mut arena = Arena::new();
+        let id = arena.intern(&parsed(expr));
+        let df = arena.diff(id, "x").unwrap();
+        assert_eq!(arena.to_expr(df).to_string(), expected);
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let mut arena = Arena::new();
+        let (x, zero) = (arena.var("x"), arena.constant(0.0));
+
+        let nan = arena.binary(Op::Div, zero, zero);
+        assert_eq!(arena.get(nan), &Node::Binary(Op::Div, zero, zero));
+
+        let div = arena.binary(Op::Div, x, zero);
+        let f = arena.binary(Op::Add, div, x);
+        assert_eq!(
+            arena.diff(f, "x"),
+            Err(DiffError::DivisionByZero("(/ x 0)".to_string()))
+        );
+    }
+
+    #[test]
+    fn kept_division_by_zero() {
+        let mut arena = Arena::new();
+        let (x, zero) = (arena.var("x"), arena.constant(0.0));
+        let div = arena.binary(Op::Div, x, zero);
+
+        let product = arena.binary(Op::Mul, zero, div);
+        assert_eq!(arena.get(product), &Node::Binary(Op::Mul, zero, div));
+        assert_eq!(
+            arena.diff(product, "x"),
+            Err(DiffError::DivisionByZero("(/ x 0)".to_string()))
+        );
+
+        let difference = arena.binary(Op::Sub, div, div);
+        assert_eq!(arena.get(difference), &Node::Binary(Op::Sub, div, div));
+        let quotient = arena.binary(Op::Div, div, div);
+        assert_eq!(arena.get(quotient), &Node::Binary(Op::Div, div, div));
+
+        // Operands without a division by zero are still simplified
+        let sin = arena.unary(Func::Sin, x);
+        assert_eq!(arena.binary(Op::Mul, zero, sin), zero);
+    }
+
+    #[test]
+    fn unsupported_power() {
+        let mut arena = Arena::new();
+        let pow = arena.intern(&"(^ -2 (* 3 x))".parse().unwrap());
+        let f = arena.unary(Func::Sin, pow);
+
+        assert_eq!(
+            arena.diff(f, "x"),
+            Err(DiffError::UnsupportedForm("(^ -2 (* 3 x))".to_string()))
+        );
+        assert!(arena.diff(f, "y").is_ok());
+    }
+
+    #[test]
+    fn polynomial_growth() {
+        // Nested products `f(k + 1) = sin(f(k)) * f(k)` whose trees double in size with each level
+        let mut arena = Arena::new();
+        let mut f = arena.var("x");
+        for _ in 0..30 {
+            let sin = arena.unary(Func::Sin, f);
+            f = arena.binary(Op::Mul, sin, f);
+        }
+
+        assert_eq!(arena.dag_size(f), 61);
+        assert!(arena.tree_size(f) > 1 << 30);
+
+        let mut df = f;
+        for _ in 0..3 {
+            df = arena.diff(df, "x").unwrap();
+        }
+
+        assert!(arena.dag_size(df) < 5_000, "{}", arena.dag_size(df));
+        assert!(arena.tree_size(df) > 1 << 40);
+    }
+}
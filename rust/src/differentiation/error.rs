@@ -0,0 +1,57 @@
+//! Typed errors of parsing, differentiation and evaluation of expressions (see
+//! [try_diff](super::try_diff))
+
+/// Reasons why an expression can't be parsed, differentiated or evaluated, where `at` is the byte
+/// offset of the offending token
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+pub enum DiffError {
+    /// Malformed expression, e.g. with unbalanced parentheses or a missing operand
+    #[error("{message} at {at}")]
+    Syntax { message: String, at: usize },
+    /// Application of a name which is neither a function nor a binary operator
+    #[error("Unknown function '{name}' at {at}")]
+    UnknownFunction { name: String, at: usize },
+    /// Function or a binary operator applied to a wrong number of operands
+    #[error("'{name}' at {at} expects {expected} operand(s), got {actual}")]
+    Arity {
+        name: String,
+        expected: usize,
+        actual: usize,
+        at: usize,
+    },
+    /// Division by a (sub-)expression which simplifies to zero, e.g. `(/ x (- x x))`
+    #[error("Division by zero in '{0}'")]
+    DivisionByZero(String),
+    /// Expression whose derivative is not a real expression, e.g. the power `(^ -2 x)` of a
+    /// non-positive base to a variable exponent
+    #[error("Unsupported derivative form '{0}'")]
+    UnsupportedForm(String),
+    /// Expression with more than one variable, which only has partial derivatives (see
+    /// [diff_wrt](super::diff_wrt))
+    #[error("Ambiguous variable of differentiation in '{0}', use a partial derivative")]
+    AmbiguousVariable(String),
+    /// Variable which is not bound to a value by an evaluation (see [eval](super::eval))
+    #[error("Unbound variable '{0}'")]
+    UnboundVariable(String),
+    /// Evaluation outside of the domain of a function or an operator, e.g. `(ln x)` for `x = 0`
+    #[error("'{expr}' is undefined {reason}")]
+    Undefined { expr: String, reason: String },
+}
+
+impl DiffError {
+    #[inline]
+    pub(super) fn syntax(message: impl Into<String>, at: usize) -> Self {
+        Self::Syntax {
+            message: message.into(),
+            at,
+        }
+    }
+
+    #[inline]
+    pub(super) fn undefined(expr: impl ToString, reason: impl Into<String>) -> Self {
+        Self::Undefined {
+            expr: expr.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
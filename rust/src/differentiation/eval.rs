@@ -0,0 +1,189 @@
+//! Numeric evaluation and symbolic substitution of [Expr]essions
+
+use super::{DiffError, Expr, Func, FuncExpr, Op, OpExpr};
+use std::sync::Arc;
+
+/// Evaluates given expression with variables bound in `env`.
+///
+/// Fails if a variable is unbound or if the expression is evaluated outside of the domain of some
+/// function or operator (e.g. `ln` of a non-positive value, `tan` at its poles or division by zero).
+pub fn eval(expr: &Expr, env: &[(&str, f64)]) -> Result<f64, DiffError> {
+    match expr {
+        Expr::Const(c) => Ok(*c),
+        Expr::Var(v) => env
+            .iter()
+            .find_map(|(name, value)| (name == v).then_some(*value))
+            .ok_or_else(|| DiffError::UnboundVariable(v.clone())),
+        Expr::Unary(FuncExpr { f, arg }) => {
+            let x = eval(arg, env)?;
+            match f {
+                Func::Sin => Ok(x.sin()),
+                Func::Cos => Ok(x.cos()),
+                // `|cos x|` is the distance of `x` to the nearest pole, which is undefined if `x` can
+                // not be told apart from the pole at its own precision
+                Func::Tan if x.cos().abs() <= f64::EPSILON * x.abs().max(1.0) => {
+                    Err(DiffError::undefined(expr, format!("at its pole {x}")))
+                }
+                Func::Tan => Ok(x.tan()),
+                Func::Exp => Ok(x.exp()),
+                Func::Ln if x <= 0.0 => Err(DiffError::undefined(expr, format!("for {x}"))),
+                Func::Ln => Ok(x.ln()),
+                Func::Sqrt if x < 0.0 => Err(DiffError::undefined(expr, format!("for {x}"))),
+                Func::Sqrt => Ok(x.sqrt()),
+                Func::Asin | Func::Acos if x.abs() > 1.0 => {
+                    Err(DiffError::undefined(expr, format!("for {x}")))
+                }
+                Func::Asin => Ok(x.asin()),
+                Func::Acos => Ok(x.acos()),
+                Func::Atan => Ok(x.atan()),
+                Func::Sinh => Ok(x.sinh()),
+                Func::Cosh => Ok(x.cosh()),
+                Func::Tanh => Ok(x.tanh()),
+                Func::Abs => Ok(x.abs()),
+                Func::Sign if x == 0.0 => Ok(0.0),
+                Func::Sign => Ok(x.signum()),
+            }
+        }
+        Expr::Binary(OpExpr { lhs, op, rhs }) => {
+            let x = eval(lhs, env)?;
+            let y = eval(rhs, env)?;
+            match op {
+                Op::Add => Ok(x + y),
+                Op::Sub => Ok(x - y),
+                Op::Mul => Ok(x * y),
+                Op::Div if y == 0.0 => Err(DiffError::DivisionByZero(expr.to_string())),
+                Op::Div => Ok(x / y),
+                Op::Pow => match x.powf(y) {
+                    z if z.is_nan() => Err(DiffError::undefined(expr, format!("for {x}^{y}"))),
+                    z => Ok(z),
+                },
+                Op::Log if x <= 0.0 || y <= 0.0 || y == 1.0 => {
+                    Err(DiffError::undefined(expr, format!("for log({x}, {y})")))
+                }
+                Op::Log => Ok(x.log(y)),
+            }
+        }
+    }
+}
+
+impl Expr {
+    /// Evaluates this expression with variables bound in `env` (see [eval])
+    #[inline]
+    pub fn eval(&self, env: &[(&str, f64)]) -> Result<f64, DiffError> {
+        eval(self, env)
+    }
+
+    /// Returns this expression with each occurrence of variable `var` replaced by `expr`.
+    ///
+    /// Note that sub-expressions which do not depend on `var` are shared with this expression.
+    pub fn substitute(&self, var: &str, expr: &Expr) -> Self {
+        self.subst(var, &Arc::new(expr.clone()))
+            .map_or_else(|| self.clone(), |e| (*e).clone())
+    }
+
+    /// Returns `None` if `var` does not occur in this expression
+    fn subst(&self, var: &str, expr: &Arc<Expr>) -> Option<Arc<Expr>> {
+        match self {
+            Self::Const(_) => None,
+            Self::Var(v) => (v == var).then(|| expr.clone()),
+            Self::Unary(FuncExpr { f, arg }) => arg.subst(var, expr).map(|arg| {
+                Arc::new(Self::Unary(FuncExpr {
+                    f: *f,
+                    arg: arg.clone(),
+                }))
+            }),
+            Self::Binary(OpExpr { lhs, op, rhs }) => {
+                match (lhs.subst(var, expr), rhs.subst(var, expr)) {
+                    (None, None) => None,
+                    (l, r) => Some(Arc::new(Self::Binary(OpExpr {
+                        lhs: l.unwrap_or_else(|| lhs.clone()),
+                        op: *op,
+                        rhs: r.unwrap_or_else(|| rhs.clone()),
+                    }))),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::differentiation::infix::parsed;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("2 * x + 1", &[("x", 3.0)], 7.0)]
+    #[case("x^y", &[("x", 2.0), ("y", 10.0)], 1024.0)]
+    #[case("sin(x)^2 + cos(x)^2", &[("x", 0.7)], 1.0)]
+    #[case("ln(exp(x)) / x", &[("x", 5.0)], 1.0)]
+    #[case("tan(0)", &[], 0.0)]
+    #[case("tan(x) * cos(x) / sin(x)", &[("x", std::f64::consts::FRAC_PI_2 - 1e-9)], 1.0)]
+    #[case("log(x, 2) + sqrt(x)", &[("x", 16.0)], 8.0)]
+    #[case("abs(x) * sign(x) + atan(1)", &[("x", -3.0)], -3.0 + std::f64::consts::FRAC_PI_4)]
+    #[case("cosh(x)^2 - sinh(x)^2 + tanh(0)", &[("x", 1.5)], 1.0)]
+    #[case("asin(x) + acos(x)", &[("x", 0.3)], std::f64::consts::FRAC_PI_2)]
+    fn evaluation(#[case] expr: &str, #[case] env: &[(&str, f64)], #[case] expected: f64) {
+        let actual = parsed(expr).eval(env).unwrap();
+        assert!((actual - expected).abs() < 1e-12, "{actual} != {expected}");
+    }
+
+    #[rstest]
+    #[case("ln(x)", &[("x", 0.0)], "'(ln x)' is undefined for 0")]
+    #[case("ln(x - 2)", &[("x", 1.0)], "'(ln (- x 2))' is undefined for -1")]
+    #[case("tan(x)", &[("x", std::f64::consts::FRAC_PI_2)], "'(tan x)' is undefined at its pole")]
+    #[case("tan(x)", &[("x", f64::from_bits(std::f64::consts::FRAC_PI_2.to_bits() + 1))], "'(tan x)' is undefined at its pole")]
+    #[case("tan(x)", &[("x", std::f64::consts::FRAC_PI_2 * -3.0)], "'(tan x)' is undefined at its pole")]
+    #[case("tan(x)", &[("x", std::f64::consts::FRAC_PI_2 * 2000001.0)], "'(tan x)' is undefined at its pole")]
+    #[case("1 / (x - 1)", &[("x", 1.0)], "Division by zero in '(/ 1 (- x 1))'")]
+    #[case("x^0.5", &[("x", -4.0)], "'(^ x 0.5)' is undefined for -4^0.5")]
+    #[case("x + y", &[("x", 1.0)], "Unbound variable 'y'")]
+    #[case("sqrt(x)", &[("x", -1.0)], "'(sqrt x)' is undefined for -1")]
+    #[case("asin(x)", &[("x", 2.0)], "'(asin x)' is undefined for 2")]
+    #[case("log(x, 1)", &[("x", 2.0)], "'(log x 1)' is undefined for log(2, 1)")]
+    fn domain_errors(#[case] expr: &str, #[case] env: &[(&str, f64)], #[case] error: &str) {
+        let actual = parsed(expr).eval(env).unwrap_err().to_string();
+        assert!(actual.starts_with(error), "{actual}");
+    }
+
+    #[test]
+    fn substitution() {
+        let f = parsed("sin(x) + x * y");
+        let g = parsed("2 * t");
+
+        assert_eq!(
+            f.substitute("x", &g).infix().to_string(),
+            "sin(2 * t) + 2 * t * y"
+        );
+        assert_eq!(f.substitute("z", &g).infix().to_string(), "sin(x) + x * y");
+
+        // Composition is consistent with evaluation: f(g(t), y) at t = 0.25, y = 3
+        let fg = f
+            .substitute("x", &g)
+            .eval(&[("t", 0.25), ("y", 3.0)])
+            .unwrap();
+        let x = g.eval(&[("t", 0.25)]).unwrap();
+        assert_eq!(fg, f.eval(&[("x", x), ("y", 3.0)]).unwrap());
+    }
+
+    #[rstest]
+    #[case("x^3 * ln(x) + exp(2 * x) / x")]
+    #[case("x^x + asin(x / 3) - acos(x / 4) + sqrt(x + 1)")]
+    #[case("log(x, 2) * atan(x) + tanh(x) * abs(x - 2) + cosh(x) / sinh(x)")]
+    #[case("log(10, x + 1) + x^sin(x)")]
+    fn derivative_check(#[case] expr: &str) {
+        // Compare the symbolic derivative to a central difference quotient
+        let f = parsed(expr);
+        let df = f.derivative("x").unwrap();
+
+        for x in [0.5, 1.0, 2.5] {
+            let h = 1e-6;
+            let fd =
+                (f.eval(&[("x", x + h)]).unwrap() - f.eval(&[("x", x - h)]).unwrap()) / (2.0 * h);
+            let actual = df.eval(&[("x", x)]).unwrap();
+            assert!(
+                (actual - fd).abs() < 1e-5 * fd.abs().max(1.0),
+                "{actual} != {fd}"
+            );
+        }
+    }
+}
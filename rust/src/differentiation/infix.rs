@@ -0,0 +1,411 @@
+//! Infix notation for [Expr]essions
+//!
+//! Parsing is implemented as a *recursive descent* with the following grammar (in order of
+//! increasing precedence):
+//! ```text
+//! sum     := product (('+' | '-') product)*
+//! product := unary (('*' | '/') unary)*
+//! unary   := '-' unary | power
+//! power   := primary ('^' unary)?
+//! primary := number | var | func '(' sum ')' | func unary | 'log' '(' sum ',' sum ')' | '(' sum ')'
+//! ```
+//!
+//! So `^` is right-associative and binds tighter than unary negation (i.e. `-x^2 = -(x^2)`), and
+//! functions can be applied implicitly (without parentheses) to an argument which extends over
+//! powers, i.e. `sin x^2 = sin(x^2)` whereas `sin(x)^2 = (sin x)^2`.
+
+use super::{DiffError, Expr, Func, FuncExpr, Op, OpExpr};
+use std::{
+    fmt::{Display, Write},
+    sync::Arc,
+};
+
+/// Parses an expression in infix notation (e.g. `2*sin(x)^2`) into the same [Expr] which would be
+/// produced from the equivalent prefix notation (e.g. `(* 2 (^ (sin x) 2))`).
+pub fn parse(s: &str) -> Result<Expr, DiffError> {
+    let tokens = tokenize(s)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        len: s.len(),
+    };
+    let expr = parser.sum()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some((token, at)) => Err(DiffError::syntax(format!("Unexpected '{token}'"), *at)),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(Op),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Num(n) => write!(f, "{n}"),
+            Self::Ident(id) => f.write_str(id),
+            Self::Op(op) => write!(f, "{op}"),
+            Self::LeftParen => f.write_char('('),
+            Self::RightParen => f.write_char(')'),
+            Self::Comma => f.write_char(','),
+        }
+    }
+}
+
+/// Splits given string into [Token]s paired with their byte offsets
+fn tokenize(s: &str) -> Result<Vec<(Token, usize)>, DiffError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some(&(at, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            ',' => Token::Comma,
+            '+' | '-' | '*' | '/' | '^' => Token::Op(c.to_string().parse()?),
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = at;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_digit() || c == '.') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let num = &s[at..end];
+                let num = num.parse().map_err(|_| {
+                    DiffError::syntax(format!("Failed to parse a number from '{num}'"), at)
+                })?;
+                tokens.push((Token::Num(num), at));
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = at;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                tokens.push((Token::Ident(s[at..end].to_owned()), at));
+                continue;
+            }
+            c => return Err(DiffError::syntax(format!("Unexpected character '{c}'"), at)),
+        };
+        chars.next();
+        tokens.push((token, at));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// Length of the input, i.e. the offset of its end
+    len: usize,
+}
+
+impl Parser {
+    #[inline]
+    fn peek(&self) -> Option<&(Token, usize)> {
+        self.tokens.get(self.pos)
+    }
+
+    #[inline]
+    fn next(&mut self) -> Option<(Token, usize)> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consumes the next token if it's one of given binary operators
+    fn next_op(&mut self, ops: &[Op]) -> Option<Op> {
+        match self.peek() {
+            Some((Token::Op(op), _)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, DiffError> {
+        let mut lhs = self.product()?;
+        while let Some(op) = self.next_op(&[Op::Add, Op::Sub]) {
+            lhs = binary(op, lhs, self.product()?)?;
+        }
+        Ok(lhs)
+    }
+
+    fn product(&mut self) -> Result<Expr, DiffError> {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.next_op(&[Op::Mul, Op::Div]) {
+            lhs = binary(op, lhs, self.unary()?)?;
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, DiffError> {
+        if self.next_op(&[Op::Sub]).is_some() {
+            return Ok(-self.unary()?);
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, DiffError> {
+        let base = self.primary()?;
+        if self.next_op(&[Op::Pow]).is_some() {
+            return binary(Op::Pow, base, self.unary()?);
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, DiffError> {
+        match self.next() {
+            Some((Token::Num(n), _)) => Ok(Expr::Const(n)),
+            Some((Token::Ident(id), _)) => match id.parse::<Func>() {
+                Ok(f) => {
+                    // Explicit application binds just the parenthesized argument
+                    let arg = if let Some((Token::LeftParen, _)) = self.peek() {
+                        self.primary()?
+                    } else {
+                        self.unary()?
+                    };
+                    Ok(Expr::Unary(FuncExpr {
+                        f,
+                        arg: Arc::new(arg),
+                    }))
+                }
+                Err(_) if id == "log" => self.log(),
+                Err(_) => Ok(Expr::Var(id)),
+            },
+            Some((Token::LeftParen, at)) => {
+                let expr = self.sum()?;
+                match self.next() {
+                    Some((Token::RightParen, _)) => Ok(expr),
+                    _ => Err(DiffError::syntax("Unmatched '('", at)),
+                }
+            }
+            Some((token, at)) => Err(DiffError::syntax(format!("Unexpected '{token}'"), at)),
+            None => Err(DiffError::syntax("Unexpected end of expression", self.len)),
+        }
+    }
+}
+
+impl Parser {
+    /// Parses arguments `(x, b)` of a logarithm of `x` with base `b`
+    fn log(&mut self) -> Result<Expr, DiffError> {
+        self.expect(Token::LeftParen)?;
+        let x = self.sum()?;
+        self.expect(Token::Comma)?;
+        let base = self.sum()?;
+        self.expect(Token::RightParen)?;
+        binary(Op::Log, x, base)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), DiffError> {
+        match self.next() {
+            Some((token, _)) if token == expected => Ok(()),
+            Some((token, at)) => Err(DiffError::syntax(
+                format!("Expected '{expected}' but got '{token}'"),
+                at,
+            )),
+            None => Err(DiffError::syntax(
+                format!("Expected '{expected}'"),
+                self.len,
+            )),
+        }
+    }
+}
+
+/// Creates a validated and simplified binary expression, just like the prefix parser does
+#[inline]
+fn binary(op: Op, lhs: Expr, rhs: Expr) -> Result<Expr, DiffError> {
+    Expr::binary(op, lhs, rhs)
+}
+
+/// [Display] wrapper which renders an [Expr] in infix notation with minimal parentheses
+pub struct Infix<'a>(pub(super) &'a Expr);
+
+/// Precedence levels of infix expressions
+pub(super) const SUM: u8 = 1;
+pub(super) const PRODUCT: u8 = 2;
+pub(super) const NEG: u8 = 3;
+pub(super) const POWER: u8 = 4;
+pub(super) const ATOM: u8 = 5;
+
+/// Returns the operand of `expr` if it represents a negation (i.e. `(* -1 x)` or `(- 0 x)`)
+pub(super) fn negated(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::Binary(OpExpr { lhs, op, rhs }) => match (op, lhs.as_ref()) {
+            (Op::Mul, Expr::Const(c)) if *c == -1.0 => Some(rhs),
+            (Op::Sub, Expr::Const(c)) if *c == 0.0 => Some(rhs),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+pub(super) fn prec(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Const(c) if c.is_sign_negative() => NEG,
+        Expr::Const(_) | Expr::Var(_) | Expr::Unary(_) => ATOM,
+        e if negated(e).is_some() => NEG,
+        Expr::Binary(OpExpr { op, .. }) => match op {
+            Op::Add | Op::Sub => SUM,
+            Op::Mul | Op::Div => PRODUCT,
+            Op::Pow => POWER,
+            Op::Log => ATOM,
+        },
+    }
+}
+
+impl Infix<'_> {
+    fn fmt_operand(
+        &self,
+        expr: &Expr,
+        parens: bool,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if parens {
+            write!(f, "({})", Infix(expr))
+        } else {
+            write!(f, "{}", Infix(expr))
+        }
+    }
+}
+
+impl Display for Infix<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(arg) = negated(self.0) {
+            f.write_char('-')?;
+            return self.fmt_operand(arg, prec(arg) <= NEG, f);
+        }
+
+        match self.0 {
+            Expr::Const(c) => write!(f, "{c}"),
+            Expr::Var(v) => f.write_str(v),
+            Expr::Unary(FuncExpr { f: func, arg }) => write!(f, "{func}({})", Infix(arg)),
+            Expr::Binary(OpExpr {
+                lhs,
+                op: Op::Log,
+                rhs,
+            }) => write!(f, "log({}, {})", Infix(lhs), Infix(rhs)),
+            Expr::Binary(OpExpr { lhs, op, rhs }) => {
+                let p = prec(self.0);
+                let (l, r) = (prec(lhs), prec(rhs));
+
+                if let Op::Pow = op {
+                    // Right-associative, so only the base needs parentheses on equal precedence
+                    self.fmt_operand(lhs, l <= p, f)?;
+                    f.write_char('^')?;
+                    self.fmt_operand(rhs, r < p, f)
+                } else {
+                    // Left-associative, so the right operand needs parentheses on equal precedence
+                    self.fmt_operand(lhs, l < p, f)?;
+                    write!(f, " {op} ")?;
+                    self.fmt_operand(rhs, r <= p, f)
+                }
+            }
+        }
+    }
+}
+
+/// Parses an infix expression known to be valid (shared by the tests of the other modules)
+#[cfg(test)]
+pub(super) fn parsed(s: &str) -> Expr {
+    parse(s).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("x", "x")]
+    #[case("-2.5", "-2.5")]
+    #[case("1 + 2 * 3", "7")]
+    #[case("2*sin(x)^2", "(* 2 (^ (sin x) 2))")]
+    #[case("2 * sin x^2", "(* 2 (sin (^ x 2)))")]
+    #[case("x - y - z", "(- (- x y) z)")]
+    #[case("x / y / z", "(/ (/ x y) z)")]
+    #[case("x ^ y ^ z", "(^ x (^ y z))")]
+    #[case("-x^2", "(* -1 (^ x 2))")]
+    #[case("(-x)^2", "(^ (* -1 x) 2)")]
+    #[case("x^-2", "(^ x -2)")]
+    #[case("exp(1 * x) / (x - x + 1)", "(exp x)")]
+    #[case("ln(cos(x + 1))", "(ln (cos (+ x 1)))")]
+    #[case("tan -x", "(tan (* -1 x))")]
+    #[case("log(x + 1, 2)", "(log (+ x 1) 2)")]
+    #[case("log(8, 2) * sqrt x", "(* 3 (sqrt x))")]
+    #[case("abs(sinh x) - atan(x)^2", "(- (abs (sinh x)) (^ (atan x) 2))")]
+    #[trace]
+    fn parse_infix(#[case] infix: &str, #[case] prefix: &str) {
+        let expr = parse(infix).expect("valid infix expression");
+        assert_eq!(prefix, expr.to_string());
+    }
+
+    #[rstest]
+    #[case("(+ x 1)", "x + 1")]
+    #[case("(* 2 (+ x 1))", "2 * (x + 1)")]
+    #[case("(- x (- y z))", "x - (y - z)")]
+    #[case("(- (- x y) z)", "x - y - z")]
+    #[case("(/ x (* y z))", "x / (y * z)")]
+    #[case("(^ (^ x y) z)", "(x^y)^z")]
+    #[case("(^ x (^ y z))", "x^y^z")]
+    #[case("(^ x -1)", "x^(-1)")]
+    #[case("(^ -2 x)", "(-2)^x")]
+    #[case("(* -1 (sin x))", "-sin(x)")]
+    #[case("(* -1 (+ x 1))", "-(x + 1)")]
+    #[case("(* -1 (^ x 2))", "-x^2")]
+    #[case("(^ (* -1 x) 2)", "(-x)^2")]
+    #[case("(/ -2 (^ (+ 1 x) 2))", "-2 / (1 + x)^2")]
+    #[case("(* 2 (^ (sin x) 2))", "2 * sin(x)^2")]
+    #[case("(^ (log x 10) 2)", "log(x, 10)^2")]
+    #[case("(* (asin x) (acos (tanh x)))", "asin(x) * acos(tanh(x))")]
+    #[trace]
+    fn display_infix(#[case] prefix: &str, #[case] infix: &str) {
+        let expr = prefix.parse::<Expr>().expect("valid prefix expression");
+        assert_eq!(infix, expr.infix().to_string());
+
+        // infix output must parse back to the very same expression
+        let parsed = parse(infix).expect("valid infix expression");
+        assert_eq!(expr.to_string(), parsed.to_string());
+    }
+
+    #[rstest]
+    #[case("(x + 1", "Unmatched '(' at 0")]
+    #[case("x + ", "Unexpected end of expression at 4")]
+    #[case("x + )", "Unexpected ')' at 4")]
+    #[case("x y", "Unexpected 'y' at 2")]
+    #[case("x $ 1", "Unexpected character '$' at 2")]
+    #[case("1.2.3", "Failed to parse a number from '1.2.3' at 0")]
+    #[case("x / (y - y)", "Division by zero in '(/ x 0)'")]
+    #[case("log(x)", "Expected ',' but got ')' at 5")]
+    #[case("log x, 2", "Expected '(' but got 'x' at 4")]
+    #[case("log(x, 2", "Expected ')' at 8")]
+    #[trace]
+    fn parse_failures(#[case] infix: &str, #[case] expected: &str) {
+        assert_eq!(
+            Err(expected.to_string()),
+            parse(infix)
+                .map(|e| e.to_string())
+                .map_err(|e| e.to_string())
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Symbolic integration of [Expr]essions
+//!
+//! Antiderivatives are found by a small set of rules:
+//!  - Linearity, i.e. sums are integrated term by term and factors which don't depend on the
+//!    variable of integration are pulled out of the integral
+//!  - Power rule `∫ u^n = u^(n + 1) / (n + 1)` and `∫ 1/u = ln|u|`
+//!  - Elementary functions `exp`, `sin`, `cos` and exponentials `c^u`
+//!
+//! where `u` is a linear substitution `a*x + b` for the variable of integration `x`.
+//!
+//! If no rule applies to the simplified expression, integration is re-tried on the expanded one
+//! (so that e.g. products of polynomials can be integrated term by term).
+
+use super::{Expr, Func, FuncExpr, Op, OpExpr};
+use crate::approx;
+
+/// Returns an antiderivative (without the integration constant) of `expr` with respect to `var`
+/// or `None` if it can't be found by any of the supported rules.
+pub fn integrate(expr: &Expr, var: &str) -> Option<Expr> {
+    integral(&expr.simplified(), var)
+        .or_else(|| integral(&expr.expanded(), var))
+        .map(|expr| expr.simplified())
+}
+
+fn integral(expr: &Expr, x: &str) -> Option<Expr> {
+    use Op::*;
+
+    if !expr.depends_on(x) {
+        return Some(binary(Mul, expr.clone(), Expr::Var(x.to_owned())));
+    }
+
+    match expr {
+        Expr::Const(_) => unreachable!("constants do not depend on any variable"),
+        Expr::Var(_) => power(expr, 1.0, x),
+        Expr::Unary(FuncExpr { f, arg }) => {
+            let a = linear(arg, x)?;
+            let f = match f {
+                Func::Exp => expr.clone(),
+                Func::Sin => binary(Mul, Expr::Const(-1.0), unary(Func::Cos, arg)),
+                Func::Cos => unary(Func::Sin, arg),
+                _ => return None,
+            };
+            Some(binary(Div, f, a))
+        }
+        Expr::Binary(OpExpr { lhs, op, rhs }) => match op {
+            Add | Sub => Some(binary(*op, integral(lhs, x)?, integral(rhs, x)?)),
+            Mul if !lhs.depends_on(x) => Some(binary(Mul, (**lhs).clone(), integral(rhs, x)?)),
+            Mul if !rhs.depends_on(x) => Some(binary(Mul, integral(lhs, x)?, (**rhs).clone())),
+            Div if !rhs.depends_on(x) => Some(binary(Div, integral(lhs, x)?, (**rhs).clone())),
+            Div if !lhs.depends_on(x) => {
+                let rhs = match rhs.as_ref() {
+                    Expr::Binary(OpExpr {
+                        lhs: u,
+                        op: Pow,
+                        rhs: n,
+                    }) => match n.as_ref() {
+                        Expr::Const(n) => power(u, -n, x),
+                        _ => None,
+                    },
+                    u => power(u, -1.0, x),
+                };
+                Some(binary(Mul, (**lhs).clone(), rhs?))
+            }
+            Pow => match (lhs.as_ref(), rhs.as_ref()) {
+                (u, Expr::Const(n)) => power(u, *n, x),
+                // ∫ c^u = c^u / (a * ln(c))
+                (c, u) if !c.depends_on(x) => {
+                    let a = linear(u, x)?;
+                    let ln_c = unary(Func::Ln, c);
+                    Some(binary(Div, expr.clone(), binary(Mul, a, ln_c)))
+                }
+                _ => None,
+            },
+            _ => None,
+        },
+    }
+}
+
+/// Integrates `u^n` where `u` is linear in `x`
+fn power(u: &Expr, n: f64, x: &str) -> Option<Expr> {
+    use Op::*;
+
+    let a = linear(u, x)?;
+    if n == -1.0 {
+        let ln = unary(Func::Ln, &unary(Func::Abs, u));
+        Some(binary(Div, ln, a))
+    } else {
+        let u = binary(Pow, u.clone(), Expr::Const(n + 1.0));
+        Some(binary(Div, u, binary(Mul, Expr::Const(n + 1.0), a)))
+    }
+}
+
+/// Returns the (non-zero) coefficient `a` if `u = a*x + b` where neither `a` nor `b` depend on `x`
+fn linear(u: &Expr, x: &str) -> Option<Expr> {
+    let a = u.derivative(x).ok()?.simplified();
+    match a {
+        Expr::Const(a) if approx!(a, 0) => None,
+        a if a.depends_on(x) => None,
+        a => Some(a),
+    }
+}
+
+#[inline]
+fn binary(op: Op, lhs: Expr, rhs: Expr) -> Expr {
+    (op, lhs, rhs).into()
+}
+
+#[inline]
+fn unary(f: Func, arg: &Expr) -> Expr {
+    Expr::Unary(FuncExpr {
+        f,
+        arg: arg.clone().into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::differentiation::infix::parsed;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("3", "3 * x")]
+    #[case("y", "x * y")]
+    #[case("x", "0.5 * x^2")]
+    #[case("3 * x^2 + 2 * x + 1", "x^3 + x^2 + x")]
+    #[case("x^-2", "-1 / x")]
+    #[case("1 / x", "ln(abs(x))")]
+    #[case("exp(x) + sin(x) - cos(x)", "-sin(x) - cos(x) + exp(x)")]
+    #[case("cos(2 * x + 1)", "0.5 * sin(2 * x + 1)")]
+    #[case("exp(-x)", "-exp(-x)")]
+    #[case("(3 * x + 1)^2", "0.1111111111111111 * (3 * x + 1)^3")]
+    #[case("2 / (x - 1)", "2 * ln(abs(x - 1))")]
+    #[case("2^x", "2^x / ln(2)")]
+    #[case("(x + 1) * (x - 1)", "0.3333333333333333 * x^3 - x")]
+    #[case("(x^2 + 1) / x", "0.5 * x^2 + ln(abs(x))")]
+    #[case("x * y^2", "0.5 * x^2 * y^2")]
+    #[trace]
+    fn antiderivatives(#[case] expr: &str, #[case] expected: &str) {
+        let actual = integrate(&parsed(expr), "x").expect("integrable expression");
+        assert_eq!(actual.infix().to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("x^2 * exp(3*x - 2)")]
+    #[case("sin(x) * cos(x)")]
+    #[case("ln(x)")]
+    #[case("tan(x)")]
+    #[case("1 / (x^2 + 1)")]
+    #[case("exp(x^2)")]
+    #[case("x^x")]
+    #[trace]
+    fn unsupported(#[case] expr: &str) {
+        assert!(integrate(&parsed(expr), "x").is_none());
+    }
+
+    #[rstest]
+    #[case("3 * x^2 + 2 * x + 1")]
+    #[case("(2 * x - 1)^5 + 1 / (4 - x)")]
+    #[case("exp(2 * x) * 3 - sin(0.5 * x + 1) / 2")]
+    #[case("(x - 2) * (x + 3)^2 + 5^(1 - x)")]
+    #[case("x^-0.5 + cos(-x)")]
+    #[trace]
+    fn differentiates_back(#[case] expr: &str) {
+        // d/dx ∫ f = f at a few sample points
+        let f = parsed(expr);
+        let df = integrate(&f, "x").unwrap().derivative("x").unwrap();
+
+        for x in [0.25, 1.5, 3.0] {
+            let (expected, actual) = (f.eval(&[("x", x)]).unwrap(), df.eval(&[("x", x)]).unwrap());
+            assert!(
+                (actual - expected).abs() < 1e-9 * expected.abs().max(1.0),
+                "{actual} != {expected}"
+            );
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! LaTeX rendering of [Expr]essions
+//!
+//! Fractions are rendered with `\frac`, powers as superscripts and functions by their LaTeX
+//! commands (e.g. `\sin`). Parentheses are placed only where the precedence of operators requires
+//! them (just like in the [Infix](super::Infix) notation), except for function arguments and
+//! negated right operands of binary operators (e.g. `x \cdot \left(-y\right)`) which are always
+//! parenthesized.
+
+use super::infix::{negated, prec, ATOM, NEG, POWER, PRODUCT};
+use super::{Expr, Func, FuncExpr, Op, OpExpr};
+use std::fmt::Display;
+
+/// [Display] wrapper which renders an [Expr] as a LaTeX formula (without math delimiters)
+pub struct Latex<'a>(pub(super) &'a Expr);
+
+impl Expr {
+    /// Returns a [Display] wrapper which renders this expression in LaTeX
+    #[inline]
+    pub fn latex(&self) -> Latex<'_> {
+        Latex(self)
+    }
+}
+
+/// Precedence of an expression in LaTeX, where fractions behave like atoms
+pub(super) fn latex_prec(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Binary(OpExpr { op: Op::Div, .. }) if negated(expr).is_none() => ATOM,
+        _ => prec(expr),
+    }
+}
+
+/// Returns `true` if a constant factor can be juxtaposed with `expr`, as in `2 x`, `3 \sin(x)` or
+/// `2 (x + 1)`
+pub(super) fn juxtaposable(expr: &Expr) -> bool {
+    if latex_prec(expr) <= PRODUCT {
+        // The operand will be parenthesized
+        return negated(expr).is_none();
+    }
+
+    match expr {
+        Expr::Var(_) | Expr::Unary(_) => true,
+        Expr::Binary(OpExpr {
+            lhs, op: Op::Pow, ..
+        }) => matches!(lhs.as_ref(), Expr::Var(_) | Expr::Unary(_)),
+        _ => false,
+    }
+}
+
+fn command(f: Func) -> &'static str {
+    match f {
+        Func::Sin => "\\sin",
+        Func::Cos => "\\cos",
+        Func::Tan => "\\tan",
+        Func::Exp => "\\exp",
+        Func::Ln => "\\ln",
+        Func::Sqrt => "\\sqrt",
+        Func::Asin => "\\arcsin",
+        Func::Acos => "\\arccos",
+        Func::Atan => "\\arctan",
+        Func::Sinh => "\\sinh",
+        Func::Cosh => "\\cosh",
+        Func::Tanh => "\\tanh",
+        Func::Abs => "\\operatorname{abs}",
+        Func::Sign => "\\operatorname{sgn}",
+    }
+}
+
+/// Renders a variable name with LaTeX special characters escaped (e.g. `x_1` as `x\_1`)
+struct Escaped<'a>(&'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '#' | '$' | '%' | '&' | '_' | '{' | '}' => write!(f, "\\{c}")?,
+                '\\' => f.write_str("\\backslash{}")?,
+                '^' => f.write_str("\\hat{}")?,
+                '~' => f.write_str("\\sim{}")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Latex<'_> {
+    fn fmt_operand(
+        &self,
+        expr: &Expr,
+        parens: bool,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if parens {
+            write!(f, "\\left({}\\right)", Latex(expr))
+        } else {
+            write!(f, "{}", Latex(expr))
+        }
+    }
+}
+
+impl Display for Latex<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(arg) = negated(self.0) {
+            f.write_str("-")?;
+            return self.fmt_operand(arg, latex_prec(arg) <= NEG, f);
+        }
+
+        match self.0 {
+            Expr::Const(c) => write!(f, "{c}"),
+            Expr::Var(v) if v.chars().count() == 1 => write!(f, "{}", Escaped(v)),
+            Expr::Var(v) => write!(f, "\\mathit{{{}}}", Escaped(v)),
+            Expr::Unary(FuncExpr { f: func, arg }) => match func {
+                Func::Sqrt => write!(f, "\\sqrt{{{}}}", Latex(arg)),
+                Func::Abs => write!(f, "\\left|{}\\right|", Latex(arg)),
+                func => write!(f, "{}\\left({}\\right)", command(*func), Latex(arg)),
+            },
+            Expr::Binary(OpExpr { lhs, op, rhs }) => {
+                let p = latex_prec(self.0);
+                let (l, r) = (latex_prec(lhs), latex_prec(rhs));
+
+                match op {
+                    Op::Div => write!(f, "\\frac{{{}}}{{{}}}", Latex(lhs), Latex(rhs)),
+                    Op::Log => write!(f, "\\log_{{{}}}\\left({}\\right)", Latex(rhs), Latex(lhs)),
+                    Op::Pow => {
+                        // Fractions need parentheses as bases, whereas exponents are grouped
+                        let frac = matches!(lhs.as_ref(), Expr::Binary(OpExpr { op: Op::Div, .. }));
+                        self.fmt_operand(lhs, l <= POWER || frac, f)?;
+                        write!(f, "^{{{}}}", Latex(rhs))
+                    }
+                    Op::Mul => {
+                        self.fmt_operand(lhs, l < p, f)?;
+                        match lhs.as_ref() {
+                            Expr::Const(c) if *c >= 0.0 && juxtaposable(rhs) => f.write_str(" ")?,
+                            _ => f.write_str(" \\cdot ")?,
+                        }
+                        self.fmt_operand(rhs, r <= p || r == NEG, f)
+                    }
+                    Op::Add | Op::Sub => {
+                        self.fmt_operand(lhs, l < p, f)?;
+                        write!(f, " {op} ")?;
+                        self.fmt_operand(rhs, r <= p || r == NEG, f)
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::differentiation::{infix, Expr};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("x + 1", "x + 1")]
+    #[case("2 * x", "2 x")]
+    #[case("2 * 3^x", "2 \\cdot 3^{x}")]
+    #[case("x * y", "x \\cdot y")]
+    #[case("2 * sin(x)^2", "2 \\sin\\left(x\\right)^{2}")]
+    #[case("(x + 1) / (x - 1)", "\\frac{x + 1}{x - 1}")]
+    #[case("2 * (x + 1) / y", "\\frac{2 \\left(x + 1\\right)}{y}")]
+    #[case("(x / y)^2", "\\left(\\frac{x}{y}\\right)^{2}")]
+    #[case("(x + 1)^(2 * n)", "\\left(x + 1\\right)^{2 n}")]
+    #[case("x^y^z", "x^{y^{z}}")]
+    #[case("(-x)^2", "\\left(-x\\right)^{2}")]
+    #[case("x - (y - z)", "x - \\left(y - z\\right)")]
+    #[case("-(x + 1)", "-\\left(x + 1\\right)")]
+    #[case(
+        "exp(-x) * cos(2 * x)",
+        "\\exp\\left(-x\\right) \\cdot \\cos\\left(2 x\\right)"
+    )]
+    #[case("sqrt(x^2 + 1) + abs(x)", "\\sqrt{x^{2} + 1} + \\left|x\\right|")]
+    #[case(
+        "log(x, 2) - atan(alpha)",
+        "\\log_{2}\\left(x\\right) - \\arctan\\left(\\mathit{alpha}\\right)"
+    )]
+    #[case("-2.5 * x", "-2.5 \\cdot x")]
+    #[case("x * -y - -2", "x \\cdot \\left(-y\\right) - \\left(-2\\right)")]
+    #[trace]
+    fn latex(#[case] infix: &str, #[case] expected: &str) {
+        let expr: Expr = infix::parse(infix).unwrap();
+        assert_eq!(expr.latex().to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("_", "\\_")]
+    #[case("x_1", "\\mathit{x\\_1}")]
+    #[case("a&b%", "\\mathit{a\\&b\\%}")]
+    #[case("{#$}", "\\mathit{\\{\\#\\$\\}}")]
+    #[case("\\^~", "\\mathit{\\backslash{}\\hat{}\\sim{}}")]
+    #[trace]
+    fn escaped_names(#[case] name: &str, #[case] expected: &str) {
+        assert_eq!(Expr::Var(name.to_owned()).latex().to_string(), expected);
+    }
+}
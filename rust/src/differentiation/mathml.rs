@@ -0,0 +1,208 @@
+//! Presentation MathML rendering of [Expr]essions
+//!
+//! The layout follows the [Latex](super::Latex) renderer, i.e. fractions are rendered as `<mfrac>`,
+//! powers as `<msup>` and parentheses are placed only where the precedence of operators requires
+//! them (except for function arguments and negated right operands).
+
+use super::infix::{negated, NEG, POWER};
+use super::latex::{juxtaposable, latex_prec as mathml_prec};
+use super::{Expr, Func, FuncExpr, Op, OpExpr};
+use std::fmt::Display;
+
+/// [Display] wrapper which renders an [Expr] as a presentation MathML `<math>` element
+pub struct MathMl<'a>(pub(super) &'a Expr);
+
+impl Expr {
+    /// Returns a [Display] wrapper which renders this expression in presentation MathML
+    #[inline]
+    pub fn mathml(&self) -> MathMl<'_> {
+        MathMl(self)
+    }
+}
+
+const MINUS: &str = "<mo>&#x2212;</mo>";
+const TIMES: &str = "<mo>&#x22C5;</mo>";
+const INVISIBLE_TIMES: &str = "<mo>&#x2062;</mo>";
+const APPLY_FUNCTION: &str = "<mo>&#x2061;</mo>";
+
+/// Returns the conventional name of a function (which might differ from the parsed one)
+fn name(f: Func) -> String {
+    match f {
+        Func::Asin => "arcsin".to_owned(),
+        Func::Acos => "arccos".to_owned(),
+        Func::Atan => "arctan".to_owned(),
+        Func::Sign => "sgn".to_owned(),
+        f => f.to_string(),
+    }
+}
+
+/// Renders text with the XML special characters `<`, `>` and `&` replaced by entities
+struct Escaped<'a>(&'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '&' => f.write_str("&amp;")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders the contents of a `<math>` element
+struct Node<'a>(&'a Expr);
+
+impl Node<'_> {
+    fn fmt_operand(
+        &self,
+        expr: &Expr,
+        parens: bool,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if parens {
+            write!(f, "<mrow><mo>(</mo>{}<mo>)</mo></mrow>", Node(expr))
+        } else {
+            write!(f, "{}", Node(expr))
+        }
+    }
+}
+
+impl Display for Node<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(arg) = negated(self.0) {
+            write!(f, "<mrow>{MINUS}")?;
+            self.fmt_operand(arg, mathml_prec(arg) <= NEG, f)?;
+            return f.write_str("</mrow>");
+        }
+
+        match self.0 {
+            Expr::Const(c) if c.is_sign_negative() => {
+                write!(f, "<mrow>{MINUS}<mn>{}</mn></mrow>", -c)
+            }
+            Expr::Const(c) => write!(f, "<mn>{c}</mn>"),
+            Expr::Var(v) => write!(f, "<mi>{}</mi>", Escaped(v)),
+            Expr::Unary(FuncExpr { f: func, arg }) => match func {
+                Func::Sqrt => write!(f, "<msqrt>{}</msqrt>", Node(arg)),
+                Func::Abs => write!(f, "<mrow><mo>|</mo>{}<mo>|</mo></mrow>", Node(arg)),
+                func => write!(
+                    f,
+                    "<mrow><mi>{}</mi>{APPLY_FUNCTION}<mrow><mo>(</mo>{}<mo>)</mo></mrow></mrow>",
+                    name(*func),
+                    Node(arg)
+                ),
+            },
+            Expr::Binary(OpExpr { lhs, op, rhs }) => {
+                let p = mathml_prec(self.0);
+                let (l, r) = (mathml_prec(lhs), mathml_prec(rhs));
+
+                match op {
+                    Op::Div => write!(f, "<mfrac>{}{}</mfrac>", Node(lhs), Node(rhs)),
+                    Op::Log => write!(
+                        f,
+                        "<mrow><msub><mi>log</mi>{}</msub>{APPLY_FUNCTION}\
+                         <mrow><mo>(</mo>{}<mo>)</mo></mrow></mrow>",
+                        Node(rhs),
+                        Node(lhs)
+                    ),
+                    Op::Pow => {
+                        let frac = matches!(lhs.as_ref(), Expr::Binary(OpExpr { op: Op::Div, .. }));
+                        f.write_str("<msup>")?;
+                        self.fmt_operand(lhs, l <= POWER || frac, f)?;
+                        write!(f, "{}</msup>", Node(rhs))
+                    }
+                    Op::Mul => {
+                        f.write_str("<mrow>")?;
+                        self.fmt_operand(lhs, l < p, f)?;
+                        match lhs.as_ref() {
+                            Expr::Const(c) if *c >= 0.0 && juxtaposable(rhs) => {
+                                f.write_str(INVISIBLE_TIMES)?
+                            }
+                            _ => f.write_str(TIMES)?,
+                        }
+                        self.fmt_operand(rhs, r <= p || r == NEG, f)?;
+                        f.write_str("</mrow>")
+                    }
+                    Op::Add | Op::Sub => {
+                        f.write_str("<mrow>")?;
+                        self.fmt_operand(lhs, l < p, f)?;
+                        f.write_str(if let Op::Add = op {
+                            "<mo>+</mo>"
+                        } else {
+                            MINUS
+                        })?;
+                        self.fmt_operand(rhs, r <= p || r == NEG, f)?;
+                        f.write_str("</mrow>")
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Display for MathMl<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{}</math>",
+            Node(self.0)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::differentiation::{infix, Expr};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("x + 1", "<mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow>")]
+    #[case("2 * x", "<mrow><mn>2</mn><mo>&#x2062;</mo><mi>x</mi></mrow>")]
+    #[case("x * y", "<mrow><mi>x</mi><mo>&#x22C5;</mo><mi>y</mi></mrow>")]
+    #[case("2 * (x + y)", "<mrow><mn>2</mn><mo>&#x2062;</mo><mrow><mo>(</mo><mrow><mi>x</mi><mo>+</mo><mi>y</mi></mrow><mo>)</mo></mrow></mrow>")]
+    #[case(
+        "x - -2",
+        "<mrow><mi>x</mi><mo>&#x2212;</mo><mrow><mo>(</mo><mrow><mo>&#x2212;</mo><mn>2</mn></mrow><mo>)</mo></mrow></mrow>"
+    )]
+    #[case(
+        "(x + 1) / y",
+        "<mfrac><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow><mi>y</mi></mfrac>"
+    )]
+    #[case("(x / y)^2", "<msup><mrow><mo>(</mo><mfrac><mi>x</mi><mi>y</mi></mfrac><mo>)</mo></mrow><mn>2</mn></msup>")]
+    #[case(
+        "-x^2",
+        "<mrow><mo>&#x2212;</mo><msup><mi>x</mi><mn>2</mn></msup></mrow>"
+    )]
+    #[case(
+        "sin(x)",
+        "<mrow><mi>sin</mi><mo>&#x2061;</mo><mrow><mo>(</mo><mi>x</mi><mo>)</mo></mrow></mrow>"
+    )]
+    #[case(
+        "sqrt(abs(x))",
+        "<msqrt><mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow></msqrt>"
+    )]
+    #[case("log(x, 2)", "<mrow><msub><mi>log</mi><mn>2</mn></msub><mo>&#x2061;</mo><mrow><mo>(</mo><mi>x</mi><mo>)</mo></mrow></mrow>")]
+    #[trace]
+    fn mathml(#[case] infix: &str, #[case] expected: &str) {
+        let expr: Expr = infix::parse(infix).unwrap();
+        assert_eq!(
+            expr.mathml().to_string(),
+            format!("<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{expected}</math>")
+        );
+    }
+
+    #[rstest]
+    #[case("a<b", "<mi>a&lt;b</mi>")]
+    #[case("x>&y", "<mi>x&gt;&amp;y</mi>")]
+    #[case("&amp;", "<mi>&amp;amp;</mi>")]
+    #[trace]
+    fn escaped_names(#[case] name: &str, #[case] expected: &str) {
+        assert_eq!(
+            Expr::Var(name.to_owned()).mathml().to_string(),
+            format!("<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{expected}</math>")
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Multivariate differentiation of [Expr]essions
+
+use super::{DiffError, Expr};
+
+/// Dense row-major matrix of expressions
+pub type Matrix = Vec<Vec<Expr>>;
+
+/// Returns the vector of partial derivatives of `expr` with respect to each of `vars` (in order)
+pub fn gradient(expr: &Expr, vars: &[&str]) -> Result<Vec<Expr>, DiffError> {
+    vars.iter().map(|var| expr.derivative(var)).collect()
+}
+
+/// Returns the Jacobian matrix `J` of given vector function `exprs` with respect to `vars`, i.e.
+/// `J[i][j]` is the partial derivative of `exprs[i]` with respect to `vars[j]`
+pub fn jacobian(exprs: &[Expr], vars: &[&str]) -> Result<Matrix, DiffError> {
+    exprs.iter().map(|expr| gradient(expr, vars)).collect()
+}
+
+/// Returns the Hessian matrix `H` of `expr` with respect to `vars`, i.e. `H[i][j]` is the second
+/// order partial derivative of `expr` with respect to `vars[i]` and `vars[j]`
+pub fn hessian(expr: &Expr, vars: &[&str]) -> Result<Matrix, DiffError> {
+    jacobian(&gradient(expr, vars)?, vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(m: &[Expr]) -> Vec<String> {
+        m.iter().map(|e| e.infix().to_string()).collect()
+    }
+
+    #[test]
+    fn gradient_of_polynomial() {
+        let expr = "(+ (* x (* y y)) (* 3 x))".parse::<Expr>().unwrap();
+        let grad = gradient(&expr, &["x", "y"]).unwrap();
+        assert_eq!(render(&grad), vec!["y * y + 3", "x * (y + y)"]);
+    }
+
+    #[test]
+    fn jacobian_of_polar_coordinates() {
+        let exprs = ["(* r (cos t))", "(* r (sin t))"]
+            .iter()
+            .map(|e| e.parse::<Expr>().unwrap())
+            .collect::<Vec<_>>();
+
+        let jac = jacobian(&exprs, &["r", "t"]).unwrap();
+
+        assert_eq!(render(&jac[0]), vec!["cos(t)", "r * -sin(t)"]);
+        assert_eq!(render(&jac[1]), vec!["sin(t)", "r * cos(t)"]);
+    }
+
+    #[test]
+    fn hessian_of_product() {
+        let expr = "(+ (* (^ x 2) y) (exp (* x y)))".parse::<Expr>().unwrap();
+        let hess = hessian(&expr, &["x", "y"]).unwrap();
+
+        assert_eq!(hess.len(), 2);
+        assert_eq!(
+            render(&hess[0])[1],
+            "2 * x + (exp(x * y) + y * (x * exp(x * y)))"
+        );
+        assert_eq!(
+            render(&hess[1])[0],
+            "2 * x + (exp(x * y) + x * (y * exp(x * y)))"
+        );
+        assert_eq!(render(&hess[1])[1], "x * (x * exp(x * y))");
+
+        // Mixed partial derivatives are equal after simplification
+        assert_eq!(
+            hess[0][1].simplified().to_string(),
+            hess[1][0].simplified().to_string()
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Prefix notation parser with typed errors, which also backs the [FromStr](std::str::FromStr)
+//! implementation of [Expr]
+//!
+//! The parser works on tokens located by their byte offsets, so that it can report precisely what
+//! went wrong and where (see [DiffError]). The grammar is
+//! ```text
+//! expr := atom | '(' func expr ')' | '(' op expr expr ')'
+//! ```
+
+use super::{DiffError, Expr, Func, FuncExpr, Op, Simplify, Validated};
+use std::{fmt::Display, sync::Arc};
+
+/// Parses an expression in prefix notation (e.g. `(* 2 (^ (sin x) 2))`)
+pub(super) fn parse(s: &str) -> Result<Expr, DiffError> {
+    let mut parser = Parser {
+        tokens: tokenize(s),
+        pos: 0,
+        len: s.len(),
+    };
+    let expr = parser.expr()?;
+    match parser.next() {
+        None => Ok(expr),
+        Some((token, at)) => Err(DiffError::syntax(format!("Unexpected '{token}'"), at)),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token<'a> {
+    LeftParen,
+    RightParen,
+    Atom(&'a str),
+}
+
+impl Display for Token<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LeftParen => f.write_str("("),
+            Self::RightParen => f.write_str(")"),
+            Self::Atom(atom) => f.write_str(atom),
+        }
+    }
+}
+
+/// Splits given string into parentheses and whitespace-separated atoms paired with their byte
+/// offsets
+fn tokenize(s: &str) -> Vec<(Token<'_>, usize)> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((at, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            _ => {
+                let mut end = at + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                Token::Atom(&s[at..end])
+            }
+        };
+        tokens.push((token, at));
+    }
+
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token<'a>, usize)>,
+    pos: usize,
+    /// Length of the input, i.e. the offset of its end
+    len: usize,
+}
+
+impl<'a> Parser<'a> {
+    #[inline]
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).map(|(token, _)| *token)
+    }
+
+    #[inline]
+    fn next(&mut self) -> Option<(Token<'a>, usize)> {
+        let token = self.tokens.get(self.pos).copied();
+        self.pos += 1;
+        token
+    }
+
+    fn expr(&mut self) -> Result<Expr, DiffError> {
+        match self.next() {
+            Some((Token::Atom(atom), at)) => operand(atom, at),
+            Some((Token::LeftParen, at)) => self.application(at),
+            Some((Token::RightParen, at)) => Err(DiffError::syntax("Unexpected ')'", at)),
+            None => Err(DiffError::syntax("Unexpected end of expression", self.len)),
+        }
+    }
+
+    /// Parses the rest of a parenthesized application which starts at `start`
+    fn application(&mut self, start: usize) -> Result<Expr, DiffError> {
+        let (name, at) = match self.next() {
+            Some((Token::Atom(name), at)) => (name, at),
+            Some((Token::RightParen, _)) => {
+                return Err(DiffError::syntax("Empty expression", start))
+            }
+            Some((token, at)) => {
+                return Err(DiffError::syntax(
+                    format!("Expected a function or an operator but got '{token}'"),
+                    at,
+                ))
+            }
+            None => return Err(DiffError::syntax("Unmatched '('", start)),
+        };
+
+        let mut args = Vec::new();
+        loop {
+            match self.peek() {
+                Some(Token::RightParen) => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => args.push(self.expr()?),
+                None => return Err(DiffError::syntax("Unmatched '('", start)),
+            }
+        }
+
+        let actual = args.len();
+        let arity = |expected| DiffError::Arity {
+            name: name.to_owned(),
+            expected,
+            actual,
+            at,
+        };
+
+        if let Ok(op) = name.parse::<Op>() {
+            let [lhs, rhs]: [Expr; 2] = args.try_into().map_err(|_| arity(2))?;
+            let expr = (op, lhs, rhs).valid()?;
+            Ok(expr.simplify())
+        } else if let Ok(f) = name.parse::<Func>() {
+            let [arg]: [Expr; 1] = args.try_into().map_err(|_| arity(1))?;
+            Ok(Expr::Unary(FuncExpr {
+                f,
+                arg: Arc::new(arg),
+            }))
+        } else if name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            Err(DiffError::UnknownFunction {
+                name: name.to_owned(),
+                at,
+            })
+        } else {
+            // Symbols such as `$` or numbers can't even name a function
+            Err(DiffError::syntax(
+                format!("Expected a function or an operator but got '{name}'"),
+                at,
+            ))
+        }
+    }
+}
+
+/// Parses a constant or a variable, which must not be a function or an operator name
+fn operand(atom: &str, at: usize) -> Result<Expr, DiffError> {
+    if atom.parse::<Op>().is_ok() || atom.parse::<Func>().is_ok() {
+        return Err(DiffError::syntax(
+            format!("Expected an operand but got '{atom}'"),
+            at,
+        ));
+    }
+    Ok(Expr::atom(atom))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("x", "x")]
+    #[case("-x", "(- 0 x)")]
+    #[case("-2.5", "-2.5")]
+    #[case(" ( sin\tx ) ", "(sin x)")]
+    #[case("(+ (sin x) 2)", "(+ (sin x) 2)")]
+    #[case("(* (+ x 1) (- x -1))", "(* (+ x 1) (- x -1))")]
+    #[case("(/ (exp (* 1 x)) (+ (- x x) 1))", "(exp x)")]
+    #[case("(^ (cos (* 2 x)) (log x 2))", "(^ (cos (* 2 x)) (log x 2))")]
+    #[case("(tan (* -1 alpha))", "(tan (* -1 alpha))")]
+    #[trace]
+    fn parsed(#[case] prefix: &str, #[case] expected: &str) {
+        assert_eq!(parse(prefix).unwrap().to_string(), expected);
+        assert_eq!(prefix.parse::<Expr>().unwrap().to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("", DiffError::syntax("Unexpected end of expression", 0))]
+    #[case("(exp (+ x 1)", DiffError::syntax("Unmatched '('", 0))]
+    #[case("(exp ())", DiffError::syntax("Empty expression", 5))]
+    #[case("(sin x))", DiffError::syntax("Unexpected ')'", 7))]
+    #[case("x y", DiffError::syntax("Unexpected 'y'", 2))]
+    #[case(
+        "((sin x) 1)",
+        DiffError::syntax("Expected a function or an operator but got '('", 1)
+    )]
+    #[case("(+ x sin)", DiffError::syntax("Expected an operand but got 'sin'", 5))]
+    #[case(
+        "(* 2 (fn x))",
+        DiffError::UnknownFunction { name: "fn".to_string(), at: 6 }
+    )]
+    #[case(
+        "($ 1 2)",
+        DiffError::syntax("Expected a function or an operator but got '$'", 1)
+    )]
+    #[case(
+        "(2 x)",
+        DiffError::syntax("Expected a function or an operator but got '2'", 1)
+    )]
+    #[case(
+        "(sin x 1)",
+        DiffError::Arity { name: "sin".to_string(), expected: 1, actual: 2, at: 1 }
+    )]
+    #[case(
+        "(+ 1 (^ x))",
+        DiffError::Arity { name: "^".to_string(), expected: 2, actual: 1, at: 6 }
+    )]
+    #[case(
+        "(/ (exp x) (- x x))",
+        DiffError::DivisionByZero("(/ (exp x) 0)".to_string())
+    )]
+    #[trace]
+    fn parse_failures(#[case] prefix: &str, #[case] expected: DiffError) {
+        assert_eq!(parse(prefix).unwrap_err(), expected);
+    }
+}
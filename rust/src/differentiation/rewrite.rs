@@ -0,0 +1,590 @@
+//! Term-rewriting simplifier for [Expr]essions
+//!
+//! Each rewriting pass normalizes an expression into a *sum of terms*, where each term is a
+//! product `c * b1^e1 * ... * bn^en` of a numeric coefficient `c` and powers of distinct bases.
+//! This representation naturally implements the following rules:
+//!  - Constant folding and elimination of neutral elements (`0 + x`, `1 * x`, `x^1`, ...)
+//!  - Collection of like terms, e.g. `x*y + 2*y*x = 3*x*y`
+//!  - Combination of powers with the same base, e.g. `x*x = x^2` or `x^a / x = x^(a - 1)`
+//!  - Canonical ordering of operands of commutative operators
+//!
+//! Additionally, functions of simplified arguments are reduced by identities such as
+//! `ln(exp(x)) = x` and `sin(x)^2 + cos(x)^2 = 1` or `sin(x) / cos(x) = tan(x)`.
+//!
+//! Passes are repeated until a fixpoint is reached, i.e. until the expression does not change.
+//!
+//! Note that sums are distributed only over a single term (e.g. `2*(x + 1) = 2*x + 2`), whereas a
+//! product of sums is kept as a product (e.g. `(x + 1)*(x + 1) = (x + 1)^2`).
+
+use super::{Expr, Func, FuncExpr, Op, OpExpr};
+use crate::approx;
+use std::{cmp::Ordering, sync::Arc};
+
+/// Upper bound on the number of rewriting passes (the fixpoint is typically reached in 2 passes)
+const MAX_PASSES: usize = 16;
+
+/// Upper bound on the exponent of a sum which gets multiplied out by [Expr::expanded], larger
+/// powers are kept as they are
+const MAX_EXPANSION: f64 = 64.0;
+
+impl Expr {
+    /// Returns an equivalent expression simplified by a set of rewriting rules (see [rewrite](self))
+    pub fn simplified(&self) -> Self {
+        fixpoint(self, rewrite)
+    }
+
+    /// Like [Expr::simplified], but additionally distributes products over sums and expands
+    /// (small) non-negative integral powers of sums, e.g. `(x + 1)^2 * x = x^3 + 2*x^2 + x`
+    pub fn expanded(&self) -> Self {
+        fixpoint(self, |expr| match expr {
+            Expr::Const(_) | Expr::Var(_) => expr.clone(),
+            _ => Sum::of(expr, true).into(),
+        })
+    }
+}
+
+fn fixpoint(expr: &Expr, pass: impl Fn(&Expr) -> Expr) -> Expr {
+    let mut expr = pass(expr);
+    for _ in 1..MAX_PASSES {
+        let next = pass(&expr);
+        if cmp(&next, &expr) == Ordering::Equal {
+            break;
+        }
+        expr = next;
+    }
+    expr
+}
+
+/// Single rewriting pass
+fn rewrite(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Const(_) | Expr::Var(_) => expr.clone(),
+        _ => Sum::from(expr).into(),
+    }
+}
+
+/// Reduces a function of an already simplified argument
+fn apply(f: Func, arg: Expr) -> Expr {
+    use Expr::*;
+    use Func::*;
+
+    match (f, &arg) {
+        (Ln, Unary(FuncExpr { f: Exp, arg })) => (**arg).clone(),
+        (Exp | Cos, Const(c)) if approx!(c, 0) => 1.into(),
+        (Sin | Tan, Const(c)) if approx!(c, 0) => 0.into(),
+        (Ln | Acos, Const(c)) if approx!(c, 1) => 0.into(),
+        (Sqrt | Asin | Atan | Sinh | Tanh, Const(c)) if approx!(c, 0) => 0.into(),
+        (Cosh, Const(c)) if approx!(c, 0) => 1.into(),
+        (Sqrt, Const(c)) if approx!(c, 1) => 1.into(),
+        (Abs, Const(c)) => c.abs().into(),
+        (Sign, Const(c)) if approx!(c, 0) => 0.into(),
+        (Sign, Const(c)) => c.signum().into(),
+        (Abs | Sign, Unary(FuncExpr { f: g, .. })) if *g == f => arg.clone(),
+        _ => Unary(FuncExpr { f, arg: arg.into() }),
+    }
+}
+
+/// Product `coef * base1^exp1 * ... * baseN^expN` with bases in canonical order
+#[derive(Clone, Debug)]
+struct Term {
+    coef: f64,
+    factors: Vec<(Expr, Expr)>,
+}
+
+impl Term {
+    #[inline]
+    fn constant(coef: f64) -> Self {
+        Self {
+            coef,
+            factors: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn atom(base: Expr) -> Self {
+        Self {
+            coef: 1.0,
+            factors: vec![(base, 1.into())],
+        }
+    }
+
+    fn mul(mut self, other: Self) -> Self {
+        self.coef *= other.coef;
+        for (base, exp) in other.factors {
+            match self.factors.iter().position(|(b, _)| eq(b, &base)) {
+                Some(i) => self.factors[i].1 = add(&self.factors[i].1, &exp),
+                None => self.factors.push((base, exp)),
+            }
+        }
+        self.normalize()
+    }
+
+    /// Raises this term to an integral power `n`
+    fn powi(mut self, n: i32) -> Self {
+        self.coef = self.coef.powi(n);
+        for (_, exp) in self.factors.iter_mut() {
+            *exp = scale(exp, n as f64);
+        }
+        self.normalize()
+    }
+
+    fn normalize(mut self) -> Self {
+        use Expr::*;
+        use Func::*;
+
+        self.factors.retain(|(_, exp)| !is_const(exp, 0.0));
+
+        // Fold constant powers of constants into the coefficient
+        let coef = &mut self.coef;
+        self.factors.retain(|(base, exp)| match (base, exp) {
+            (Const(b), Const(e)) if !is_zero(*b) => {
+                *coef *= b.powf(*e);
+                false
+            }
+            _ => true,
+        });
+
+        // sin(x)^a / cos(x)^a = tan(x)^a
+        let mut i = 0;
+        while i < self.factors.len() {
+            let tan = match &self.factors[i] {
+                (Unary(FuncExpr { f: Sin, arg }), exp @ Const(_)) => {
+                    self.factors.iter().position(|(base, e)| match base {
+                        Unary(FuncExpr { f: Cos, arg: a }) => {
+                            eq(a, arg) && eq(e, &scale(exp, -1.0))
+                        }
+                        _ => false,
+                    })
+                }
+                _ => None,
+            };
+            match tan {
+                Some(j) => {
+                    let (base, exp) = self.factors.remove(i);
+                    self.factors.remove(if j < i { j } else { j - 1 });
+                    if let Unary(FuncExpr { arg, .. }) = base {
+                        self.factors.push((apply(Tan, (*arg).clone()), exp));
+                    }
+                    i = 0;
+                }
+                None => i += 1,
+            }
+        }
+
+        self.factors.sort_by(|(a, _), (b, _)| cmp(a, b));
+        self
+    }
+
+    /// Orders terms by their bases and then by descending exponents, constant terms come last
+    fn cmp(&self, other: &Self) -> Ordering {
+        for ((b1, e1), (b2, e2)) in self.factors.iter().zip(other.factors.iter()) {
+            let ord = cmp(b1, b2).then_with(|| match (e1, e2) {
+                (Expr::Const(x), Expr::Const(y)) => y.total_cmp(x),
+                _ => cmp(e2, e1),
+            });
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        other.factors.len().cmp(&self.factors.len())
+    }
+
+    /// Returns `true` iff both terms differ at most in their coefficients
+    #[inline]
+    fn is_like(&self, other: &Self) -> bool {
+        self.factors.len() == other.factors.len()
+            && self
+                .factors
+                .iter()
+                .zip(other.factors.iter())
+                .all(|((b1, e1), (b2, e2))| eq(b1, b2) && eq(e1, e2))
+    }
+}
+
+impl From<Term> for Expr {
+    fn from(term: Term) -> Self {
+        let (den, num): (Vec<_>, Vec<_>) = term
+            .factors
+            .into_iter()
+            .partition(|(_, exp)| matches!(exp, Self::Const(e) if *e < 0.0));
+
+        let num = num.into_iter().map(|(base, exp)| power(base, exp));
+        let den = den
+            .into_iter()
+            .map(|(base, exp)| power(base, scale(&exp, -1.0)));
+
+        let coef = term.coef;
+        let num = if approx!(coef, 1) {
+            product(num)
+        } else {
+            product(std::iter::once(coef.into()).chain(num))
+        };
+
+        match (num, product(den)) {
+            (num, None) => num.unwrap_or_else(|| 1.into()),
+            (num, Some(den)) => (Op::Div, num.unwrap_or_else(|| 1.into()), den).into(),
+        }
+    }
+}
+
+/// Sum of [Term]s which are pairwise unlike, an empty sum represents zero
+#[derive(Clone, Debug)]
+struct Sum(Vec<Term>);
+
+impl Sum {
+    #[inline]
+    fn constant(c: f64) -> Self {
+        Self(vec![Term::constant(c)]).normalize()
+    }
+
+    fn add(mut self, other: Self) -> Self {
+        self.0.extend(other.0);
+        self.normalize()
+    }
+
+    fn scale(mut self, c: f64) -> Self {
+        self.0.iter_mut().for_each(|term| term.coef *= c);
+        self.normalize()
+    }
+
+    fn mul(self, other: Self) -> Self {
+        match (self.single(), other.single()) {
+            (Some(x), _) => Self(other.0.into_iter().map(|t| x.clone().mul(t)).collect()),
+            (_, Some(y)) => Self(self.0.into_iter().map(|t| t.mul(y.clone())).collect()),
+            _ => Self(vec![Term::atom(self.into()).mul(Term::atom(other.into()))]),
+        }
+        .normalize()
+    }
+
+    /// Multiplies out all the pairs of terms of both sums
+    fn expand(self, other: Self) -> Self {
+        let terms = self
+            .0
+            .iter()
+            .flat_map(|x| other.0.iter().map(|y| x.clone().mul(y.clone())))
+            .collect();
+        Self(terms).normalize()
+    }
+
+    fn pow(self, exp: Expr) -> Self {
+        match (self.single(), &exp) {
+            (_, Expr::Const(e)) if approx!(e, 0) => Self::constant(1.0),
+            (_, Expr::Const(e)) if self.0.is_empty() && *e > 0.0 => self,
+            (Some(t), Expr::Const(e)) if t.factors.is_empty() => Self::constant(t.coef.powf(*e)),
+            (Some(t @ Term { coef, .. }), Expr::Const(e))
+                if e.fract() == 0.0 && e.abs() <= i32::MAX as f64 && !is_zero(*coef) =>
+            {
+                Self(vec![t.clone().powi(*e as i32)]).normalize()
+            }
+            _ if self.0.is_empty() => Self(vec![Term {
+                coef: 1.0,
+                factors: vec![(0.into(), exp)],
+            }]),
+            _ => Self(vec![Term {
+                coef: 1.0,
+                factors: vec![(self.into(), exp)],
+            }])
+            .normalize(),
+        }
+    }
+
+    /// Returns the only term of this sum (if there is exactly one)
+    #[inline]
+    fn single(&self) -> Option<&Term> {
+        match self.0.as_slice() {
+            [term] => Some(term),
+            _ => None,
+        }
+    }
+
+    fn normalize(self) -> Self {
+        let mut terms: Vec<Term> = Vec::with_capacity(self.0.len());
+
+        for term in self.0 {
+            match terms.iter_mut().find(|t| t.is_like(&term)) {
+                Some(t) => t.coef += term.coef,
+                None => terms.push(term),
+            }
+        }
+
+        terms.retain(|t| !is_zero(t.coef));
+        terms.sort_by(Term::cmp);
+
+        match Self::pythagorean(&terms) {
+            Some((i, j, rest)) => {
+                // c * sin(x)^2 * t + c * cos(x)^2 * t = c * t
+                terms[i].factors = rest;
+                terms.remove(j);
+                Self(terms).normalize()
+            }
+            None => Self(terms),
+        }
+    }
+
+    /// Finds a pair of terms `c * sin(x)^2 * t` and `c * cos(x)^2 * t` and returns their indices
+    /// together with the factors of `t`
+    #[allow(clippy::type_complexity)]
+    fn pythagorean(terms: &[Term]) -> Option<(usize, usize, Vec<(Expr, Expr)>)> {
+        let squared = |term: &Term, f: Func| -> Option<Arc<Expr>> {
+            term.factors.iter().find_map(|(base, exp)| match base {
+                Expr::Unary(FuncExpr { f: g, arg }) if *g == f && is_const(exp, 2.0) => {
+                    Some(arg.clone())
+                }
+                _ => None,
+            })
+        };
+
+        let rest = |term: &Term, f: Func, arg: &Expr| -> Vec<(Expr, Expr)> {
+            term.factors
+                .iter()
+                .filter(|(base, exp)| match base {
+                    Expr::Unary(FuncExpr { f: g, arg: a }) => {
+                        !(*g == f && eq(a, arg) && is_const(exp, 2.0))
+                    }
+                    _ => true,
+                })
+                .cloned()
+                .collect()
+        };
+
+        for (i, sin) in terms.iter().enumerate() {
+            let Some(x) = squared(sin, Func::Sin) else {
+                continue;
+            };
+            let sin_rest = rest(sin, Func::Sin, &x);
+
+            for (j, cos) in terms.iter().enumerate() {
+                let coef = sin.coef;
+                if approx!(coef, cos.coef)
+                    && matches!(squared(cos, Func::Cos), Some(y) if eq(&x, &y))
+                {
+                    let cos_rest = rest(cos, Func::Cos, &x);
+                    if sin_rest.len() == cos_rest.len()
+                        && sin_rest
+                            .iter()
+                            .zip(cos_rest.iter())
+                            .all(|(f, g)| eq_factor(f, g))
+                    {
+                        return Some((i, j, sin_rest));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl From<&Expr> for Sum {
+    #[inline]
+    fn from(expr: &Expr) -> Self {
+        Self::of(expr, false)
+    }
+}
+
+impl Sum {
+    /// Converts given expression to a sum, optionally expanding products and powers of sums
+    fn of(expr: &Expr, expand: bool) -> Self {
+        use Op::*;
+
+        match expr {
+            Expr::Const(c) => Self::constant(*c),
+            Expr::Var(_) => Self(vec![Term::atom(expr.clone())]),
+            Expr::Unary(FuncExpr { f, arg }) => match apply(*f, rewrite(arg)) {
+                e @ Expr::Unary(_) => Self(vec![Term::atom(e)]),
+                e => Self::of(&e, expand),
+            },
+            Expr::Binary(OpExpr { lhs, op: Log, rhs }) => match (rewrite(lhs), rewrite(rhs)) {
+                (Expr::Const(x), Expr::Const(b)) => Self::constant(x.log(b)),
+                (x, b) => Self(vec![Term::atom((Log, x, b).into())]),
+            },
+            Expr::Binary(OpExpr { lhs, op, rhs }) => {
+                let lhs = Self::of(lhs, expand);
+                match op {
+                    Add => lhs.add(Self::of(rhs, expand)),
+                    Sub => lhs.add(Self::of(rhs, expand).scale(-1.0)),
+                    Mul if expand => lhs.expand(Self::of(rhs, expand)),
+                    Mul => lhs.mul(Self::of(rhs, expand)),
+                    Div if expand => lhs.expand(Self::of(rhs, expand).pow((-1).into())),
+                    Div => lhs.mul(Self::of(rhs, expand).pow((-1).into())),
+                    Pow => match rewrite(rhs) {
+                        Expr::Const(n)
+                            if expand && n > 0.0 && n <= MAX_EXPANSION && n.fract() == 0.0 =>
+                        {
+                            (1..n as usize).fold(lhs.clone(), |acc, _| acc.expand(lhs.clone()))
+                        }
+                        exp => lhs.pow(exp),
+                    },
+                    Log => unreachable!("logarithms are rewritten separately"),
+                }
+            }
+        }
+    }
+}
+
+impl From<Sum> for Expr {
+    fn from(sum: Sum) -> Self {
+        let mut terms = sum.0.into_iter();
+
+        let Some(first) = terms.next() else {
+            return 0.into();
+        };
+
+        terms.fold(first.into(), |acc, mut term| {
+            if term.coef < 0.0 {
+                term.coef = -term.coef;
+                (Op::Sub, acc, term.into()).into()
+            } else {
+                (Op::Add, acc, term.into()).into()
+            }
+        })
+    }
+}
+
+// Helper functions
+
+/// Total canonical order of expressions: constants < variables < functions < binary operations
+fn cmp(a: &Expr, b: &Expr) -> Ordering {
+    use Expr::*;
+
+    fn rank(e: &Expr) -> u8 {
+        match e {
+            Const(_) => 0,
+            Var(_) => 1,
+            Unary(_) => 2,
+            Binary(_) => 3,
+        }
+    }
+
+    match (a, b) {
+        (Const(x), Const(y)) => x.total_cmp(y),
+        (Var(x), Var(y)) => x.cmp(y),
+        (Unary(f), Unary(g)) => f.f.cmp(&g.f).then_with(|| cmp(&f.arg, &g.arg)),
+        (Binary(x), Binary(y)) => {
+            x.op.cmp(&y.op)
+                .then_with(|| cmp(&x.lhs, &y.lhs))
+                .then_with(|| cmp(&x.rhs, &y.rhs))
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+#[inline]
+fn eq(a: &Expr, b: &Expr) -> bool {
+    cmp(a, b) == Ordering::Equal
+}
+
+#[inline]
+fn eq_factor((b1, e1): &(Expr, Expr), (b2, e2): &(Expr, Expr)) -> bool {
+    eq(b1, b2) && eq(e1, e2)
+}
+
+#[inline]
+fn is_zero(c: f64) -> bool {
+    approx!(c, 0)
+}
+
+#[inline]
+fn is_const(expr: &Expr, c: f64) -> bool {
+    matches!(expr, Expr::Const(x) if approx!(x, c))
+}
+
+#[inline]
+fn add(a: &Expr, b: &Expr) -> Expr {
+    Sum::from(a).add(Sum::from(b)).into()
+}
+
+#[inline]
+fn scale(a: &Expr, c: f64) -> Expr {
+    Sum::from(a).scale(c).into()
+}
+
+#[inline]
+fn power(base: Expr, exp: Expr) -> Expr {
+    if is_const(&exp, 1.0) {
+        base
+    } else {
+        (Op::Pow, base, exp).into()
+    }
+}
+
+fn product(factors: impl Iterator<Item = Expr>) -> Option<Expr> {
+    factors.reduce(|acc, f| (Op::Mul, acc, f).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::differentiation::{infix, simplify, Notation};
+
+    fn simplified(expr: &str) -> String {
+        infix::parse(expr).unwrap().simplified().infix().to_string()
+    }
+
+    #[test]
+    fn like_terms() {
+        assert_eq!(simplified("x + x"), "2 * x");
+        assert_eq!(simplified("x*y + 2*y*x - x"), "3 * x * y - x");
+        assert_eq!(simplified("x - x"), "0");
+        assert_eq!(simplified("2*(x + 1) - 2"), "2 * x");
+        assert_eq!(simplified("1 + x + 2 + x^2"), "x^2 + x + 3");
+    }
+
+    #[test]
+    fn powers() {
+        assert_eq!(simplified("x*x"), "x^2");
+        assert_eq!(simplified("x^2 * x^3 / x"), "x^4");
+        assert_eq!(simplified("x^a * x"), "x^(a + 1)");
+        assert_eq!(simplified("(2*x)^2"), "4 * x^2");
+        assert_eq!(simplified("(x + 1)*(x + 1)"), "(x + 1)^2");
+        assert_eq!(simplified("x / y^2"), "x / y^2");
+        assert_eq!(simplified("x^0 + y^1"), "y + 1");
+        assert_eq!(simplified("(2*x)^3000000000"), "(2 * x)^3000000000");
+        assert_eq!(simplify("(^ x 10000000000)"), "(^ x 10000000000)");
+    }
+
+    #[test]
+    fn expansion() {
+        let expanded = |expr| infix::parse(expr).unwrap().expanded().infix().to_string();
+
+        assert_eq!(expanded("(x + 1)^2 * x"), "x^3 + 2 * x^2 + x");
+        assert_eq!(expanded("(x + y)*(x - y)"), "x^2 - y^2");
+        assert_eq!(expanded("(x^2 + 1) / x"), "x + 1 / x");
+        assert_eq!(
+            expanded("sin(x)*(cos(x) + sin(x))"),
+            "sin(x)^2 + sin(x) * cos(x)"
+        );
+        assert_eq!(expanded("(x + 1)^-1"), "1 / (x + 1)");
+        assert_eq!(expanded("(x + 1)^100000"), "(x + 1)^100000");
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(simplified("ln(exp(x + x))"), "2 * x");
+        assert_eq!(simplified("exp(0) + ln(1) + sin(x - x)"), "1");
+        assert_eq!(simplified("sin(x)^2 + cos(x)^2"), "1");
+        assert_eq!(simplified("3*y*cos(2*x)^2 + 3*sin(x + x)^2*y"), "3 * y");
+        assert_eq!(simplified("sin(x) / cos(x)"), "tan(x)");
+    }
+
+    #[test]
+    fn canonical_ordering() {
+        assert_eq!(simplified("y*x + 1"), simplified("1 + x*y"));
+        assert_eq!(simplified("sin(x)*(x + 1)"), simplified("(1 + x)*sin(x)"));
+        assert_eq!(simplify("(* (+ 1 x) (+ y 2))"), "(* (+ x 1) (+ y 2))");
+    }
+
+    #[test]
+    fn derivatives() {
+        let df = |expr: &str| {
+            let expr = Notation::Infix.parse(expr).unwrap();
+            let df = expr.derivative("x").unwrap().simplified();
+            Notation::Infix.render(&df)
+        };
+
+        assert_eq!(df("x^3 + 2*x"), "3 * x^2 + 2");
+        assert_eq!(df("x * x * x"), "3 * x^2");
+        assert_eq!(df("tan(x)"), "1 / cos(x)^2");
+        assert_eq!(df("sin(x)^2"), "2 * sin(x) * cos(x)");
+        assert_eq!(df("ln(exp(x^2))"), "2 * x");
+    }
+}
@@ -0,0 +1,146 @@
+//! Higher-order derivatives and Taylor polynomials of single-variable [Expr]essions
+
+use super::{DiffError, Expr, IntoDiff, Op};
+
+impl Expr {
+    /// Returns the `n`-th derivative of this expression with respect to its only variable.
+    ///
+    /// Each intermediate derivative is simplified to prevent the expression from growing rapidly.
+    pub fn nth_derivative(&self, n: usize) -> Result<Self, DiffError> {
+        (0..n).try_fold(self.simplified(), |df, _| {
+            df.into_diff().map(|df| df.simplified())
+        })
+    }
+
+    /// Returns the Taylor polynomial `Σ f^(k)(a) / k! * (x - a)^k` of order `order` of this
+    /// expression `f` around the point `x = a`, where `x` is the only variable of `f`.
+    ///
+    /// The polynomial is ordered by descending powers of `x - a` and terms with zero coefficients are
+    /// omitted. Fails if `f` has more than one variable (just like [Expr::nth_derivative]) or if `f`
+    /// or any of its derivatives can't be evaluated at `a`.
+    pub fn taylor(&self, a: f64, order: usize) -> Result<Self, DiffError> {
+        let vars = self.vars();
+        let mut vars = vars.iter();
+        let x = match (vars.next(), vars.next()) {
+            (Some(x), None) => Self::Var(x.to_string()),
+            (None, _) => return self.eval(&[]).map(Self::Const),
+            (Some(_), Some(_)) => return Err(DiffError::AmbiguousVariable(self.to_string())),
+        };
+
+        let dx: Self = if a == 0.0 {
+            x.clone()
+        } else {
+            (Op::Sub, x.clone(), Self::Const(a)).into()
+        };
+
+        let var = x.to_string();
+        let env = [(var.as_str(), a)];
+
+        let mut df = self.simplified();
+        let mut factorial = 1.0;
+        let mut coefs = vec![df.eval(&env)?];
+
+        for k in 1..=order {
+            df = df.into_diff()?.simplified();
+            factorial *= k as f64;
+            coefs.push(df.eval(&env)? / factorial);
+        }
+
+        // Note: The result is not simplified as a whole, because that would expand `c * (x - a)`
+        let terms = coefs
+            .into_iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, c)| *c != 0.0)
+            .map(|(k, c)| {
+                let power = match k {
+                    0 => None,
+                    1 => Some(dx.clone()),
+                    _ => Some((Op::Pow, dx.clone(), Self::Const(k as f64)).into()),
+                };
+                (c, power)
+            });
+
+        let poly = terms.fold(None, |poly, (c, power)| {
+            let term = |c: f64| match power {
+                Some(p) if c == 1.0 => p,
+                Some(p) => (Op::Mul, Self::Const(c), p).into(),
+                None => Self::Const(c),
+            };
+            Some(match poly {
+                None => term(c),
+                Some(poly) if c < 0.0 => (Op::Sub, poly, term(-c)).into(),
+                Some(poly) => (Op::Add, poly, term(c)).into(),
+            })
+        });
+
+        Ok(poly.unwrap_or(Self::Const(0.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::differentiation::{infix::parsed, DiffError};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("x^5", 0, "x^5")]
+    #[case("x^5", 2, "20 * x^3")]
+    #[case("x^5", 5, "120")]
+    #[case("x^5", 6, "0")]
+    #[case("sin(x)", 2, "-sin(x)")]
+    #[case("sin(x)", 4, "sin(x)")]
+    #[case("exp(2 * x)", 3, "8 * exp(2 * x)")]
+    #[case("x * ln(x)", 3, "-1 / x^2")]
+    fn higher_order_derivatives(#[case] expr: &str, #[case] n: usize, #[case] expected: &str) {
+        let actual = parsed(expr).nth_derivative(n).unwrap();
+        assert_eq!(actual.infix().to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("x^3", 1.0, 3, "(x - 1)^3 + 3 * (x - 1)^2 + 3 * (x - 1) + 1")]
+    #[case("x^3", 1.0, 1, "3 * (x - 1) + 1")]
+    #[case("exp(x)", 0.0, 2, "0.5 * x^2 + x + 1")]
+    #[case("cos(x)", 0.0, 4, "0.041666666666666664 * x^4 - 0.5 * x^2 + 1")]
+    #[case("42", 1.0, 3, "42")]
+    fn taylor_polynomials(
+        #[case] expr: &str,
+        #[case] a: f64,
+        #[case] order: usize,
+        #[case] expected: &str,
+    ) {
+        let actual = parsed(expr).taylor(a, order).unwrap();
+        assert_eq!(actual.infix().to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("sin(x)", 0.0)]
+    #[case("exp(x) * cos(x)", 0.5)]
+    #[case("ln(x)", 2.0)]
+    #[case("1 / (1 - x)", -0.5)]
+    fn taylor_approximation(#[case] expr: &str, #[case] a: f64) {
+        let f = parsed(expr);
+        let p = f.taylor(a, 12).unwrap();
+
+        for dx in [-0.1, 0.0, 0.05, 0.1] {
+            let env = [("x", a + dx)];
+            let (expected, actual) = (f.eval(&env).unwrap(), p.eval(&env).unwrap());
+            assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn out_of_domain() {
+        assert!(parsed("ln(x)").taylor(0.0, 2).is_err());
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(2)]
+    fn multiple_variables(#[case] order: usize) {
+        assert_eq!(
+            parsed("x * y").taylor(0.0, order).unwrap_err(),
+            DiffError::AmbiguousVariable("(* x y)".to_string())
+        );
+    }
+}
@@ -1,28 +1,139 @@
+use crate::interval::Interval;
+use num::{BigInt, BigRational, Signed, ToPrimitive, Zero};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::{
     convert::{TryFrom, TryInto},
     fmt::{Display, Write},
-    ops::{Add, Div, Mul, Neg, Sub},
+    ops::{Add, Div, Mul, Neg, Range, Rem, Sub},
     str::FromStr,
 };
 
+mod symbolic;
+
+pub use symbolic::{compile_symbolic, parse_symbolic};
+
 pub fn calc(expr: &str) -> f64 {
-    expr.eval().unwrap()
+    try_calc(expr).unwrap()
+}
+
+/// Evaluates given expression in floating point numbers.
+///
+/// Unlike [calc], this reports invalid expressions as an [Error] which records the location of
+/// the mistake in `expr`.
+pub fn try_calc(expr: &str) -> Result<f64, Error> {
+    expr.eval()
+}
+
+/// Evaluates given expression exactly in arbitrary-precision rational numbers, so that for
+/// instance `0.1 + 0.2` is exactly `3/10`.
+///
+/// The exponent of `^` must be an integer such that the power fits into [MAX_POW_BITS] and only
+/// [Func]tions `abs`, `min` and `max` are supported, any other function is reported as
+/// [ErrorKind::Unsupported].
+pub fn calc_rational(expr: &str) -> Result<BigRational, Error> {
+    expr.eval()
+}
+
+/// Evaluates given expression in arbitrary-precision integers.
+///
+/// Division (and `%`) truncates towards zero like the primitive integer types do, decimal
+/// literals are rejected and the exponent of `^` must be non-negative (and is limited by
+/// [MAX_POW_BITS]). Only [Func]tions `abs`, `min` and `max` are supported.
+pub fn calc_integer(expr: &str) -> Result<BigInt, Error> {
+    expr.eval()
 }
 
+/// Evaluates given expression in [Interval] arithmetic, where variables are bound to intervals by
+/// `vars`.
+///
+/// The result is guaranteed to enclose the value of the expression for any choice of variables
+/// from their respective intervals (though it might not be the tightest such interval if some
+/// variable occurs multiple times in the expression).
+///
+/// Numeric literals which are not exactly representable as [f64] are enclosed by the nearest
+/// floating point numbers around them.
+pub fn calc_interval(expr: &str, vars: &[(&str, Interval)]) -> Result<Interval, Error> {
+    expr.eval_with(|name| {
+        vars.iter()
+            .find_map(|&(var, value)| if var == name { Some(value) } else { None })
+    })
+}
+
+/// Compiles given expression with variables `vars` into a [Compiled] closure which can be
+/// repeatedly evaluated in [f64] without parsing the expression again.
+///
+/// The expression is constant-folded and its `i`-th variable is bound to the `i`-th value passed to
+/// [Compiled::eval].
+pub fn compile(expr: &str, vars: &[&str]) -> Result<Compiled, Error> {
+    let node: Node = expr.eval_with(|name| vars.iter().position(|&v| v == name).map(Node::Var))?;
+    Ok(Compiled {
+        kernel: node.compile(),
+        arity: vars.len(),
+    })
+}
+
+/// Upper bound on the number of bits of exact powers, i.e. the exponent of `^` in [calc_rational]
+/// and [calc_integer] can be at most `MAX_POW_BITS / b` where `b` is the number of bits of the
+/// base (of the larger of its numerator and denominator). Larger exponents are reported as
+/// [ErrorKind::ExponentTooLarge], except for the bases `0` and `±1` whose powers are not limited.
+pub const MAX_POW_BITS: u64 = 1 << 20;
+
+/// Range of byte offsets into an expression string
+pub type Span = Range<usize>;
+
+/// An [ErrorKind] together with the [Span] of the offending part of the expression.
+///
+/// For instance, the span of an unmatched parenthesis is the parenthesis itself, the span of an
+/// operator with missing operands is the operator and the span of a function call with a wrong
+/// number of arguments is the function name.
 #[derive(thiserror::Error, Debug, PartialEq)]
-enum Error {
-    #[error("Mismatched parentheses in expression '{0}'")]
-    MismatchedParentheses(String),
+#[error("{kind} at {}..{}", .span.start, .span.end)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum ErrorKind {
+    #[error("Mismatched parenthesis")]
+    MismatchedParentheses,
     #[error("Not enough items to apply an operator to")]
     MissingArguments,
     #[error("Failed to parse '{0}'")]
     ParseFailure(String),
-    #[error("Values must be separated by an operator at '{0} {0}'")]
-    InvalidValueSeparation(f64, f64),
+    #[error("Empty expression")]
+    EmptyExpression,
+    #[error("Values must be separated by an operator")]
+    InvalidValueSeparation,
     #[error("Unknown token '{0}'")]
     UnknownToken(String),
+    #[error("Function '{0}' must be followed by '('")]
+    MissingCallParenthesis(Func),
+    #[error("Argument separator ',' outside of a function call")]
+    MisplacedSeparator,
+    #[error("Function '{func}' expects {expected} argument(s), got {actual}")]
+    Arity {
+        func: Func,
+        expected: Arity,
+        actual: usize,
+    },
+    #[error("Division by zero")]
+    DivisionByZero,
+    #[error("Unsupported operation: {0}")]
+    Unsupported(String),
+    #[error("Argument out of domain of '{0}'")]
+    OutOfDomain(String),
+    #[error("Exponent '{0}' is too large, the power would exceed {MAX_POW_BITS} bits")]
+    ExponentTooLarge(String),
+}
+
+impl ErrorKind {
+    /// Locates this error kind at given [Span]
+    #[inline]
+    fn at(self, span: Span) -> Error {
+        Error { kind: self, span }
+    }
 }
 
 /// Typeclass for types which can be *evaluated*.
@@ -30,25 +141,39 @@ enum Error {
 /// Evaluation here is generic in the sense that it produces `V` for any type for which
 /// there's an implementation of [Apply] - i.e. any *algebraic* output types (not just numbers).
 ///
-/// Note: It additionally requires [f64] to be [`Into<V>`](Into) which is due to [Token]
-/// representation of numbers.
+/// Note: It additionally requires `V` to implement [FromLiteral] so that numbers in the expression
+/// can be represented directly in the output type (without a lossy detour through [f64]).
 trait Eval {
+    /// Evaluates an expression without any variables
+    #[inline]
     fn eval<V>(self) -> Result<V, Error>
+    where
+        Self: Sized,
+        Operator: Apply<V>,
+        V: FromLiteral,
+    {
+        self.eval_with(|_| None)
+    }
+
+    /// Evaluates an expression where the value of each variable is looked up in `env`
+    fn eval_with<V, F>(self, env: F) -> Result<V, Error>
     where
         Operator: Apply<V>,
-        f64: Into<V>;
+        V: FromLiteral,
+        F: Fn(&str) -> Option<V>;
 }
 
 /// This is an implementation of the
 /// [*Shunting-yard algorithm*](https://en.wikipedia.org/wiki/Shunting-yard_algorithm) which parses
 /// and immediately evaluates expression represented by [Self].
 ///
-/// All errors are reported as variants of [Error].
+/// All errors are reported as an [Error] located at the [Span] of the offending token(s).
 impl Eval for &str {
-    fn eval<V>(self) -> Result<V, Error>
+    fn eval_with<V, F>(self, env: F) -> Result<V, Error>
     where
         Operator: Apply<V>,
-        f64: Into<V>,
+        V: FromLiteral,
+        F: Fn(&str) -> Option<V>,
     {
         // Operator stack
         let mut ops = Vec::new();
@@ -56,44 +181,106 @@ impl Eval for &str {
         // Output stack in Reverse Polish Notation (RPN)
         let mut out = Vec::new();
 
+        // Applies an operator to the output stack and locates any failure at the operator's span
+        let apply =
+            |op: Operator, span: Span, out: &mut Vec<V>| op.apply(out).map_err(|e| e.at(span));
+
         // Parse the expression string slice into a stream of `Token`s
-        for token in self.parse::<Tokens>()? {
+        for (token, span) in Tokens::parse(self, env)? {
             match token {
                 // Handle numbers
-                Token::Num(num) => out.push(num.into()),
+                Token::Num(num) => out.push(num),
 
-                // Handle both types of operators: unary negation and binary operators
+                // Prefix operators (unary negation) can't take any operand from the output yet,
+                // so these are pushed to the op stack directly
+                Token::Operator(op) if op.is_prefix() => ops.push(OpItem::Op(op, span)),
+
+                // Handle binary operators
                 Token::Operator(op) => {
                     // Apply all operators from the op stack that have higher precedence than `op`
                     //  - or the same but `op` is left-associative
                     while let Some(top) = ops.last() {
                         match top {
-                            OpItem::Op(top_op) if top_op.has_precedence(&op) => {
+                            OpItem::Op(top_op, _) if top_op.has_precedence(&op) => {
                                 // Actually remove and apply the top of the op stack
-                                if let Some(OpItem::Op(top_op)) = ops.pop() {
-                                    top_op.apply(&mut out)?
+                                if let Some(OpItem::Op(top_op, top_span)) = ops.pop() {
+                                    apply(top_op, top_span, &mut out)?
                                 }
                             }
                             _ => break,
                         }
                     }
 
-                    ops.push(OpItem::Op(op));
+                    ops.push(OpItem::Op(op, span));
                 }
 
+                // Function calls wait on the op stack for their parenthesized arguments
+                Token::Func(func) => ops.push(OpItem::Func(func, span)),
+
                 // Handle left parenthesis
-                Token::Parenthesis(ParenKind::Left) => ops.push(OpItem::LeftParenthesis),
+                Token::Parenthesis(ParenKind::Left) => ops.push(OpItem::LeftParenthesis {
+                    separators: 0,
+                    base: out.len(),
+                    span,
+                }),
+
+                // Handle argument separator
+                Token::Separator => {
+                    // Apply all operators from the op stack until we hit a left parenthesis which
+                    // must exist and belong to a function call
+                    loop {
+                        match ops.last_mut() {
+                            Some(OpItem::LeftParenthesis { separators, .. }) => {
+                                *separators += 1;
+                                break;
+                            }
+                            Some(OpItem::Op(..)) => {
+                                if let Some(OpItem::Op(op, op_span)) = ops.pop() {
+                                    apply(op, op_span, &mut out)?
+                                }
+                            }
+                            Some(OpItem::Func(..)) | None => {
+                                return Err(ErrorKind::MisplacedSeparator.at(span))
+                            }
+                        }
+                    }
+                }
 
                 // Handle right parenthesis
                 Token::Parenthesis(ParenKind::Right) => {
                     // Apply all operators from the op stack until we hit a left parenthesis or it
                     // runs out (the latter means that parentheses are mismatched)
-                    loop {
+                    let (separators, base, left) = loop {
                         match ops.pop() {
-                            Some(OpItem::Op(op)) => op.apply(&mut out)?,
-                            Some(OpItem::LeftParenthesis) => break,
-                            None => return Err(Error::MismatchedParentheses(self.to_string())),
+                            Some(OpItem::Op(op, op_span)) => apply(op, op_span, &mut out)?,
+                            Some(OpItem::LeftParenthesis {
+                                separators,
+                                base,
+                                span,
+                            }) => break (separators, base, span),
+                            Some(OpItem::Func(..)) | None => {
+                                return Err(ErrorKind::MismatchedParentheses.at(span))
+                            }
+                        }
+                    };
+
+                    // Span of the whole parenthesized group
+                    let group = left.start..span.end;
+
+                    // Each argument between the parentheses must have produced exactly one value
+                    let args = out.len() - base;
+                    if args > 0 && args != separators + 1 {
+                        return Err(ErrorKind::MissingArguments.at(group));
+                    }
+
+                    if let Some(OpItem::Func(..)) = ops.last() {
+                        if let Some(OpItem::Func(func, func_span)) = ops.pop() {
+                            apply(Operator::Call(func, args), func_span, &mut out)?;
                         }
+                    } else if separators > 0 {
+                        return Err(ErrorKind::MisplacedSeparator.at(group));
+                    } else if args == 0 {
+                        return Err(ErrorKind::MissingArguments.at(group));
                     }
                 }
             }
@@ -102,25 +289,27 @@ impl Eval for &str {
         // Process the rest of the operator stack, there should be no left parenthesis at this point
         while let Some(item) = ops.pop() {
             match item {
-                OpItem::Op(op) => op.apply(&mut out)?,
-                OpItem::LeftParenthesis => {
-                    return Err(Error::MismatchedParentheses(self.to_string()))
+                OpItem::Op(op, span) => apply(op, span, &mut out)?,
+                OpItem::Func(_, span) | OpItem::LeftParenthesis { span, .. } => {
+                    return Err(ErrorKind::MismatchedParentheses.at(span))
                 }
             }
         }
 
         // There ought to be at least one item (and if sound then exactly one)
         out.pop()
-            .ok_or_else(|| Error::ParseFailure(self.to_string()))
+            .ok_or_else(|| ErrorKind::EmptyExpression.at(0..self.len()))
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Op {
     Add,
     Sub,
     Mul,
     Div,
+    Rem,
+    Pow,
 }
 
 impl Display for Op {
@@ -130,12 +319,14 @@ impl Display for Op {
             Self::Sub => '-',
             Self::Mul => '*',
             Self::Div => '/',
+            Self::Rem => '%',
+            Self::Pow => '^',
         })
     }
 }
 
 impl FromStr for Op {
-    type Err = Error;
+    type Err = ErrorKind;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -143,7 +334,136 @@ impl FromStr for Op {
             "-" => Ok(Self::Sub),
             "*" => Ok(Self::Mul),
             "/" => Ok(Self::Div),
-            _ => Err(Error::UnknownToken(s.to_owned())),
+            "%" => Ok(Self::Rem),
+            "^" => Ok(Self::Pow),
+            _ => Err(ErrorKind::UnknownToken(s.to_owned())),
+        }
+    }
+}
+
+/// Number of arguments accepted by a [Func]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    #[inline]
+    fn accepts(&self, n: usize) -> bool {
+        match *self {
+            Self::Exactly(k) => n == k,
+            Self::AtLeast(k) => n >= k,
+        }
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exactly(k) => write!(f, "{k}"),
+            Self::AtLeast(k) => write!(f, "at least {k}"),
+        }
+    }
+}
+
+/// Built-in functions which can be called as `name(arg, ...)` in an expression
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Func {
+    Abs,
+    Sqrt,
+    Exp,
+    Ln,
+    /// Logarithm `log(x, base)` in an arbitrary base
+    Log,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Min,
+    Max,
+}
+
+impl Func {
+    fn arity(&self) -> Arity {
+        match self {
+            Self::Log => Arity::Exactly(2),
+            Self::Min | Self::Max => Arity::AtLeast(1),
+            _ => Arity::Exactly(1),
+        }
+    }
+
+    /// Returns the [f64] implementation of this function if it's unary
+    fn unary_f64(&self) -> Option<fn(f64) -> f64> {
+        match self {
+            Self::Abs => Some(f64::abs),
+            Self::Sqrt => Some(f64::sqrt),
+            Self::Exp => Some(f64::exp),
+            Self::Ln => Some(f64::ln),
+            Self::Sin => Some(f64::sin),
+            Self::Cos => Some(f64::cos),
+            Self::Tan => Some(f64::tan),
+            Self::Asin => Some(f64::asin),
+            Self::Acos => Some(f64::acos),
+            Self::Atan => Some(f64::atan),
+            Self::Sinh => Some(f64::sinh),
+            Self::Cosh => Some(f64::cosh),
+            Self::Tanh => Some(f64::tanh),
+            Self::Log | Self::Min | Self::Max => None,
+        }
+    }
+}
+
+impl Display for Func {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Abs => "abs",
+            Self::Sqrt => "sqrt",
+            Self::Exp => "exp",
+            Self::Ln => "ln",
+            Self::Log => "log",
+            Self::Sin => "sin",
+            Self::Cos => "cos",
+            Self::Tan => "tan",
+            Self::Asin => "asin",
+            Self::Acos => "acos",
+            Self::Atan => "atan",
+            Self::Sinh => "sinh",
+            Self::Cosh => "cosh",
+            Self::Tanh => "tanh",
+            Self::Min => "min",
+            Self::Max => "max",
+        })
+    }
+}
+
+impl FromStr for Func {
+    type Err = ErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "abs" => Ok(Self::Abs),
+            "sqrt" => Ok(Self::Sqrt),
+            "exp" => Ok(Self::Exp),
+            "ln" => Ok(Self::Ln),
+            "log" => Ok(Self::Log),
+            "sin" => Ok(Self::Sin),
+            "cos" => Ok(Self::Cos),
+            "tan" => Ok(Self::Tan),
+            "asin" => Ok(Self::Asin),
+            "acos" => Ok(Self::Acos),
+            "atan" => Ok(Self::Atan),
+            "sinh" => Ok(Self::Sinh),
+            "cosh" => Ok(Self::Cosh),
+            "tanh" => Ok(Self::Tanh),
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            _ => Err(ErrorKind::UnknownToken(s.to_owned())),
         }
     }
 }
@@ -164,24 +484,28 @@ impl Display for ParenKind {
 }
 
 impl FromStr for ParenKind {
-    type Err = Error;
+    type Err = ErrorKind;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "(" => Ok(Self::Left),
             ")" => Ok(Self::Right),
-            _ => Err(Error::UnknownToken(s.to_owned())),
+            _ => Err(ErrorKind::UnknownToken(s.to_owned())),
         }
     }
 }
 
 #[derive(Debug)]
 enum Operator {
-    /// Left-associative binary operator where the precedence of [Op::Mul] and [Op::Div] is higher
-    /// than that of [Op::Add] and [Op::Sub]
+    /// Binary operator where the precedence of [Op::Mul], [Op::Div] and [Op::Rem] is higher than
+    /// that of [Op::Add] and [Op::Sub]. All of these are left-associative except for [Op::Pow],
+    /// which is right-associative and binds tighter than unary negation (i.e. `-2^2 = -4`).
     Binary(Op),
-    /// Right-associative unary negation operator with higher precedence than any infix operator
+    /// Right-associative unary negation operator with higher precedence than any other infix
+    /// operator
     Neg,
+    /// Application of a function to given number of arguments, which binds the tightest
+    Call(Func, usize),
 }
 
 impl Operator {
@@ -191,17 +515,26 @@ impl Operator {
             Self::Binary(Op::Sub) => 1,
             Self::Binary(Op::Mul) => 2,
             Self::Binary(Op::Div) => 2,
+            Self::Binary(Op::Rem) => 2,
             Self::Neg => 3,
+            Self::Binary(Op::Pow) => 4,
+            Self::Call(..) => 5,
         }
     }
 
     fn is_left_assoc(&self) -> bool {
         match self {
+            Self::Binary(Op::Pow) => false,
             Self::Binary(_) => true,
-            Self::Neg => false,
+            Self::Neg | Self::Call(..) => false,
         }
     }
 
+    #[inline]
+    fn is_prefix(&self) -> bool {
+        matches!(self, Self::Neg)
+    }
+
     fn has_precedence(&self, other: &Self) -> bool {
         let self_prec = self.prec();
         let other_prec = other.prec();
@@ -212,22 +545,265 @@ impl Operator {
 trait Apply<V> {
     /// Applies [Self] to top n (*arity*) values on the `stack` and replaces these arguments on the
     /// stack by the result.
-    fn apply(self, stack: &mut Vec<V>) -> Result<(), Error>;
+    fn apply(self, stack: &mut Vec<V>) -> Result<(), ErrorKind>;
+}
+
+/// Typeclass for values which support exponentiation and application of built-in [Func]tions.
+///
+/// All operations are fallible so that value types can report those which they can't represent.
+trait Elementary: Sized + Div<Output = Self> + Rem<Output = Self> {
+    /// Division which by default delegates to [Div]
+    #[inline]
+    fn try_div(self, rhs: Self) -> Result<Self, ErrorKind> {
+        Ok(self / rhs)
+    }
+
+    /// Remainder which by default delegates to [Rem]
+    #[inline]
+    fn try_rem(self, rhs: Self) -> Result<Self, ErrorKind> {
+        Ok(self % rhs)
+    }
+
+    fn pow(self, exp: Self) -> Result<Self, ErrorKind>;
+
+    /// Applies `func` to `args`, which are guaranteed to match the [arity](Func::arity) of `func`
+    fn call(func: Func, args: Vec<Self>) -> Result<Self, ErrorKind>;
+}
+
+impl Elementary for f64 {
+    #[inline]
+    fn pow(self, exp: Self) -> Result<Self, ErrorKind> {
+        Ok(self.powf(exp))
+    }
+
+    fn call(func: Func, args: Vec<Self>) -> Result<Self, ErrorKind> {
+        let x = args[0];
+        let y = match func {
+            Func::Log => x.log(args[1]),
+            Func::Min => args.into_iter().fold(x, f64::min),
+            Func::Max => args.into_iter().fold(x, f64::max),
+            f => f.unary_f64().map_or(f64::NAN, |f| f(x)),
+        };
+        Ok(y)
+    }
+}
+
+/// Checks that the `n`-th power of an exact number with `bits` bits fits into [MAX_POW_BITS]
+fn check_pow_bits(bits: u64, n: usize) -> Result<(), ErrorKind> {
+    // A base with `bits` bits is less than `2^bits`, so its power has at most `bits * n` bits
+    if bits > 1 && bits.saturating_mul(n as u64) > MAX_POW_BITS {
+        return Err(ErrorKind::ExponentTooLarge(n.to_string()));
+    }
+    Ok(())
+}
+
+/// Shared implementation of [Elementary::call] for exact number types
+fn call_exact<V: Signed + Ord>(func: Func, args: Vec<V>) -> Result<V, ErrorKind> {
+    let mut args = args.into_iter();
+    let x = args.next().ok_or(ErrorKind::MissingArguments)?;
+    match func {
+        Func::Abs => Ok(x.abs()),
+        Func::Min => Ok(args.fold(x, V::min)),
+        Func::Max => Ok(args.fold(x, V::max)),
+        f => Err(ErrorKind::Unsupported(format!("'{f}' of an exact number"))),
+    }
+}
+
+impl Elementary for BigRational {
+    #[inline]
+    fn try_div(self, rhs: Self) -> Result<Self, ErrorKind> {
+        if rhs.is_zero() {
+            return Err(ErrorKind::DivisionByZero);
+        }
+        Ok(self / rhs)
+    }
+
+    #[inline]
+    fn try_rem(self, rhs: Self) -> Result<Self, ErrorKind> {
+        if rhs.is_zero() {
+            return Err(ErrorKind::DivisionByZero);
+        }
+        Ok(self % rhs)
+    }
+
+    fn pow(self, exp: Self) -> Result<Self, ErrorKind> {
+        if !exp.is_integer() {
+            return Err(ErrorKind::Unsupported(format!(
+                "non-integral exponent '{exp}'"
+            )));
+        }
+        let n = exp
+            .to_integer()
+            .to_isize()
+            .ok_or_else(|| ErrorKind::ExponentTooLarge(exp.to_string()))?;
+
+        let bits = self.numer().bits().max(self.denom().bits());
+        check_pow_bits(bits, n.unsigned_abs())?;
+        let pow = num::pow(self, n.unsigned_abs());
+
+        if n >= 0 {
+            Ok(pow)
+        } else if pow.is_zero() {
+            Err(ErrorKind::DivisionByZero)
+        } else {
+            Ok(pow.recip())
+        }
+    }
+
+    #[inline]
+    fn call(func: Func, args: Vec<Self>) -> Result<Self, ErrorKind> {
+        call_exact(func, args)
+    }
+}
+
+impl Elementary for BigInt {
+    #[inline]
+    fn try_div(self, rhs: Self) -> Result<Self, ErrorKind> {
+        if rhs.is_zero() {
+            return Err(ErrorKind::DivisionByZero);
+        }
+        Ok(self / rhs)
+    }
+
+    #[inline]
+    fn try_rem(self, rhs: Self) -> Result<Self, ErrorKind> {
+        if rhs.is_zero() {
+            return Err(ErrorKind::DivisionByZero);
+        }
+        Ok(self % rhs)
+    }
+
+    fn pow(self, exp: Self) -> Result<Self, ErrorKind> {
+        if exp.is_negative() {
+            return Err(ErrorKind::Unsupported(format!("integer exponent '{exp}'")));
+        }
+        let n = exp
+            .to_usize()
+            .ok_or_else(|| ErrorKind::ExponentTooLarge(exp.to_string()))?;
+        check_pow_bits(self.bits(), n)?;
+        Ok(num::pow(self, n))
+    }
+
+    #[inline]
+    fn call(func: Func, args: Vec<Self>) -> Result<Self, ErrorKind> {
+        call_exact(func, args)
+    }
+}
+
+/// Typeclass for values which can be parsed from numeric literals such as `42` or `0.25`
+trait FromLiteral: Sized {
+    fn from_literal(literal: &str) -> Result<Self, ErrorKind>;
+}
+
+impl FromLiteral for f64 {
+    #[inline]
+    fn from_literal(literal: &str) -> Result<Self, ErrorKind> {
+        literal
+            .parse()
+            .map_err(|_| ErrorKind::ParseFailure(literal.to_string()))
+    }
+}
+
+impl FromLiteral for BigRational {
+    fn from_literal(literal: &str) -> Result<Self, ErrorKind> {
+        let (int, frac) = literal.split_once('.').unwrap_or((literal, ""));
+
+        // Decimal literal `i.f` is exactly `if / 10^|f|`
+        let numer = format!("{int}{frac}")
+            .parse::<BigInt>()
+            .map_err(|_| ErrorKind::ParseFailure(literal.to_string()))?;
+        let denom = num::pow(BigInt::from(10), frac.len());
+
+        Ok(Self::new(numer, denom))
+    }
+}
+
+/// Literals which are not exactly representable are enclosed by the adjacent [f64] values
+impl FromLiteral for Interval {
+    fn from_literal(literal: &str) -> Result<Self, ErrorKind> {
+        let x = f64::from_literal(literal)?;
+
+        // Exact iff the parsed value equals the (exact) decimal value of the literal
+        let exact = BigRational::from_literal(literal)?;
+        if BigRational::from_float(x).as_ref() == Some(&exact) {
+            return Ok(Self::point(x));
+        }
+
+        Ok(Self::enclosing(x))
+    }
+}
+
+impl FromLiteral for BigInt {
+    #[inline]
+    fn from_literal(literal: &str) -> Result<Self, ErrorKind> {
+        literal
+            .parse()
+            .map_err(|_| ErrorKind::ParseFailure(literal.to_string()))
+    }
+}
+
+impl Elementary for Interval {
+    #[inline]
+    fn try_div(self, rhs: Self) -> Result<Self, ErrorKind> {
+        self.checked_div(rhs).ok_or(ErrorKind::DivisionByZero)
+    }
+
+    #[inline]
+    fn try_rem(self, rhs: Self) -> Result<Self, ErrorKind> {
+        self.checked_rem(rhs).ok_or(ErrorKind::DivisionByZero)
+    }
+
+    #[inline]
+    fn pow(self, exp: Self) -> Result<Self, ErrorKind> {
+        Interval::pow(self, exp).ok_or_else(|| ErrorKind::OutOfDomain(Op::Pow.to_string()))
+    }
+
+    fn call(func: Func, args: Vec<Self>) -> Result<Self, ErrorKind> {
+        let x = args[0];
+        let y = match func {
+            Func::Abs => Some(x.abs()),
+            Func::Sqrt => x.sqrt(),
+            Func::Exp => Some(x.exp()),
+            Func::Ln => x.ln(),
+            Func::Log => match (x.ln(), args[1].ln()) {
+                (Some(x), Some(b)) => x.checked_div(b),
+                _ => None,
+            },
+            Func::Sin => Some(x.sin()),
+            Func::Cos => Some(x.cos()),
+            Func::Tan => Some(x.tan()),
+            Func::Asin => x.asin(),
+            Func::Acos => x.acos(),
+            Func::Atan => Some(x.atan()),
+            Func::Sinh => Some(x.sinh()),
+            Func::Cosh => Some(x.cosh()),
+            Func::Tanh => Some(x.tanh()),
+            Func::Min => Some(args.into_iter().fold(x, Interval::min)),
+            Func::Max => Some(args.into_iter().fold(x, Interval::max)),
+        };
+        y.ok_or_else(|| ErrorKind::OutOfDomain(func.to_string()))
+    }
 }
 
 // Note: This impl forwards operator application to traits bounding `V` and therefore can work with
 // any *algebraic* type (e.g. one could build an expression AST)
 impl<V> Apply<V> for Operator
 where
-    V: Neg<Output = V> + Add<Output = V> + Sub<Output = V> + Mul<Output = V> + Div<Output = V>,
+    V: Neg<Output = V>
+        + Add<Output = V>
+        + Sub<Output = V>
+        + Mul<Output = V>
+        + Div<Output = V>
+        + Rem<Output = V>
+        + Elementary,
 {
-    fn apply(self, stack: &mut Vec<V>) -> Result<(), Error> {
+    fn apply(self, stack: &mut Vec<V>) -> Result<(), ErrorKind> {
         match self {
             Self::Neg => {
                 if let Some(value) = stack.pop() {
                     stack.push(value.neg())
                 } else {
-                    return Err(Error::MissingArguments);
+                    return Err(ErrorKind::MissingArguments);
                 }
             }
             Self::Binary(op) => match (stack.pop(), stack.pop()) {
@@ -236,43 +812,75 @@ where
                         Op::Add => lhs + rhs,
                         Op::Sub => lhs - rhs,
                         Op::Mul => lhs * rhs,
-                        Op::Div => lhs / rhs,
+                        Op::Div => lhs.try_div(rhs)?,
+                        Op::Rem => lhs.try_rem(rhs)?,
+                        Op::Pow => lhs.pow(rhs)?,
                     };
                     stack.push(value);
                 }
-                _ => return Err(Error::MissingArguments),
+                _ => return Err(ErrorKind::MissingArguments),
             },
+            Self::Call(func, n) => {
+                let expected = func.arity();
+                if !expected.accepts(n) {
+                    return Err(ErrorKind::Arity {
+                        func,
+                        expected,
+                        actual: n,
+                    });
+                }
+                if stack.len() < n {
+                    return Err(ErrorKind::MissingArguments);
+                }
+                let args = stack.split_off(stack.len() - n);
+                stack.push(V::call(func, args)?);
+            }
         }
         Ok(())
     }
 }
 
 /// [OpItem] represents tokens which can be placed on an operator stack
+///
+/// Each item remembers the [Span] of its token so that errors can be reported at the right place.
 #[derive(Debug)]
 enum OpItem {
-    Op(Operator),
-    LeftParenthesis,
+    Op(Operator, Span),
+    /// Function waiting for its arguments to be evaluated
+    Func(Func, Span),
+    /// Left parenthesis with the number of argument separators seen so far and the size of the
+    /// output stack at the time it was opened (to count the values produced inside)
+    LeftParenthesis {
+        separators: usize,
+        base: usize,
+        span: Span,
+    },
 }
 
 #[derive(Debug)]
-enum Token {
-    Num(f64),
+enum Token<V> {
+    Num(V),
     Operator(Operator),
+    Func(Func),
     Parenthesis(ParenKind),
+    Separator,
 }
 
 /// Try to parse an [Operator] from a state represented by a reference to previously parsed [Token]
 /// and current token - potentially the operator.
-impl TryFrom<(Option<&Token>, &str)> for Operator {
-    type Error = Error;
+impl<V> TryFrom<(Option<&Token<V>>, &str)> for Operator {
+    type Error = ErrorKind;
 
-    fn try_from(value: (Option<&Token>, &str)) -> Result<Self, Self::Error> {
+    fn try_from(value: (Option<&Token<V>>, &str)) -> Result<Self, Self::Error> {
         let (last, token) = value;
 
         let op = match (last, token.parse()?) {
             // Check for preceding operator and parenthesis to determine the unary negation
             (
-                None | Some(Token::Operator(_)) | Some(Token::Parenthesis(ParenKind::Left)),
+                None
+                | Some(Token::Operator(_))
+                | Some(Token::Parenthesis(ParenKind::Left))
+                | Some(Token::Separator),
                 Op::Sub,
             ) => Operator::Neg,
             (_, op) => Operator::Binary(op),
@@ -284,19 +892,15 @@ impl TryFrom<(Option<&Token>, &str)> for Operator {
 
 /// Try to parse a [Token] from a state represented by a reference to previously parsed [Token]
 /// and current token.
-impl TryFrom<(Option<&Self>, &str)> for Token {
-    type Error = Error;
+impl<V: FromLiteral> TryFrom<(Option<&Self>, &str)> for Token<V> {
+    type Error = ErrorKind;
 
-    fn try_from(value: (Option<&Token>, &str)) -> Result<Self, Self::Error> {
-        let (last, token) = value;
+    fn try_from(value: (Option<&Self>, &str)) -> Result<Self, Self::Error> {
+        let token = value.1;
 
         // Try to parse the token as a number
-        if let Ok(num) = token.parse() {
-            if let Some(Self::Num(prev)) = last {
-                return Err(Error::InvalidValueSeparation(*prev, num));
-            } else {
-                return Ok(Self::Num(num));
-            }
+        if token.starts_with(|c: char| c.is_ascii_digit()) {
+            return V::from_literal(token).map(Self::Num);
         }
 
         // Try to parse the token as a parenthesis
@@ -304,37 +908,86 @@ impl TryFrom<(Option<&Self>, &str)> for Token {
             return Ok(Self::Parenthesis(mode));
         }
 
-        // Finally, try to parse the token as an operator
+        if token == "," {
+            return Ok(Self::Separator);
+        }
+
+        // Try to parse the token as an operator
         if let Ok(op) = value.try_into() {
             return Ok(Self::Operator(op));
         }
 
-        Err(Error::ParseFailure(token.to_string()))
+        // Finally, try to parse the token as a function name
+        if token.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return token.parse().map(Self::Func);
+        }
+
+        Err(ErrorKind::UnknownToken(token.to_string()))
     }
 }
 
-struct Tokens(Vec<Token>);
-
-impl FromStr for Tokens {
-    type Err = Error;
+/// Stream of [Token]s, each with the [Span] it was parsed from
+struct Tokens<V>(Vec<(Token<V>, Span)>);
 
-    fn from_str(s: &str) -> Result<Self, Error> {
+impl<V: FromLiteral> Tokens<V> {
+    /// Parses given expression into a stream of [Token]s, where variables are immediately
+    /// substituted by their values from `env`.
+    ///
+    /// Note that function names are reserved and can't be used as variable names.
+    fn parse<F>(s: &str, env: F) -> Result<Self, Error>
+    where
+        F: Fn(&str) -> Option<V>,
+    {
+        // Note: The last alternative catches any other character so it's reported as unknown
         static TOKENS_RE: Lazy<Regex> = Lazy::new(|| {
-            Regex::new(r"[+\-*/()]|\d+\.\d+|\d+").expect("TOKENS_RE failed to compile")
+            Regex::new(r"[+\-*/%^(),]|\d+\.\d+|\d+|[a-zA-Z]\w*|\S")
+                .expect("TOKENS_RE failed to compile")
         });
 
-        let mut tokens = Vec::new();
+        let mut tokens: Vec<(Token<V>, Span)> = Vec::new();
+
+        for m in TOKENS_RE.find_iter(s) {
+            let (token, span) = (m.as_str(), m.range());
+            let last = tokens.last();
+
+            // Function names must be immediately followed by a call
+            if let Some((Token::Func(func), func_span)) = last {
+                if token != "(" {
+                    return Err(ErrorKind::MissingCallParenthesis(*func).at(func_span.clone()));
+                }
+            }
+
+            // Identifiers which don't name a function are variables
+            let var = token.starts_with(|c: char| c.is_ascii_alphabetic())
+                && token.parse::<Func>().is_err();
+
+            let next = match if var { env(token) } else { None } {
+                Some(value) => Token::Num(value),
+                None => (last.map(|(t, _)| t), token)
+                    .try_into()
+                    .map_err(|e: ErrorKind| e.at(span.clone()))?,
+            };
+
+            // A value can't directly follow another one (there's no implicit multiplication)
+            if let Some((Token::Num(_) | Token::Parenthesis(ParenKind::Right), prev)) = last {
+                if let Token::Num(_) | Token::Func(_) | Token::Parenthesis(ParenKind::Left) = next {
+                    return Err(ErrorKind::InvalidValueSeparation.at(prev.start..span.end));
+                }
+            }
+
+            tokens.push((next, span));
+        }
 
-        for token in TOKENS_RE.find_iter(s).map(|m| m.as_str()) {
-            tokens.push((tokens.last(), token).try_into()?);
+        if let Some((Token::Func(func), span)) = tokens.last() {
+            return Err(ErrorKind::MissingCallParenthesis(*func).at(span.clone()));
         }
 
         Ok(Self(tokens))
     }
 }
 
-impl IntoIterator for Tokens {
-    type Item = Token;
+impl<V> IntoIterator for Tokens<V> {
+    type Item = (Token<V>, Span);
     type IntoIter = <Vec<Self::Item> as IntoIterator>::IntoIter;
 
     #[inline]
@@ -343,11 +996,174 @@ impl IntoIterator for Tokens {
     }
 }
 
+// Compilation
+//  - Expressions are first evaluated (via the generic shunting-yard) into a constant-folded `Node`
+//  - A `Node` tree is then compiled into nested closures
+
+/// Expression compiled into a closure taking the values of its variables
+pub struct Compiled {
+    kernel: Kernel,
+    arity: usize,
+}
+
+impl Compiled {
+    /// Evaluates the expression with its `i`-th variable bound to `args[i]`
+    ///
+    /// # Panics
+    /// Panics if there are fewer `args` than variables of the expression
+    #[inline]
+    pub fn eval(&self, args: &[f64]) -> f64 {
+        assert!(
+            args.len() >= self.arity,
+            "expected {} arguments, got {}",
+            self.arity,
+            args.len()
+        );
+        (self.kernel)(args)
+    }
+}
+
+type Kernel = Box<dyn Fn(&[f64]) -> f64 + Send + Sync>;
+
+/// Expression tree with variables resolved to indices.
+///
+/// The algebra on nodes eagerly folds constant sub-expressions using the very same [f64]
+/// operations as [calc] does, so folding does not change results.
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Const(f64),
+    Var(usize),
+    Neg(Box<Node>),
+    Binary(Op, Box<Node>, Box<Node>),
+    Call(Func, Vec<Node>),
+}
+
+impl Node {
+    fn binary(op: Op, lhs: Self, rhs: Self) -> Self {
+        match (lhs, rhs) {
+            (Self::Const(x), Self::Const(y)) => Self::Const(match op {
+                Op::Add => x + y,
+                Op::Sub => x - y,
+                Op::Mul => x * y,
+                Op::Div => x / y,
+                Op::Rem => x % y,
+                Op::Pow => x.powf(y),
+            }),
+            (lhs, rhs) => Self::Binary(op, Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    fn compile(self) -> Kernel {
+        match self {
+            Self::Const(c) => Box::new(move |_| c),
+            Self::Var(i) => Box::new(move |args| args[i]),
+            Self::Neg(x) => {
+                let x = x.compile();
+                Box::new(move |args| -x(args))
+            }
+            Self::Binary(op, lhs, rhs) => {
+                let (f, g) = (lhs.compile(), rhs.compile());
+                match op {
+                    Op::Add => Box::new(move |args| f(args) + g(args)),
+                    Op::Sub => Box::new(move |args| f(args) - g(args)),
+                    Op::Mul => Box::new(move |args| f(args) * g(args)),
+                    Op::Div => Box::new(move |args| f(args) / g(args)),
+                    Op::Rem => Box::new(move |args| f(args) % g(args)),
+                    Op::Pow => Box::new(move |args| f(args).powf(g(args))),
+                }
+            }
+            Self::Call(func, args) => {
+                let mut fs = args.into_iter().map(Self::compile).collect::<Vec<_>>();
+                match (func, func.unary_f64()) {
+                    (_, Some(h)) => {
+                        let f = fs.remove(0);
+                        Box::new(move |args| h(f(args)))
+                    }
+                    (Func::Log, _) => {
+                        let (f, b) = (fs.remove(0), fs.remove(0));
+                        Box::new(move |args| f(args).log(b(args)))
+                    }
+                    (Func::Min, _) => {
+                        let f = fs.remove(0);
+                        Box::new(move |args| fs.iter().fold(f(args), |y, g| y.min(g(args))))
+                    }
+                    (_, _) => {
+                        let f = fs.remove(0);
+                        Box::new(move |args| fs.iter().fold(f(args), |y, g| y.max(g(args))))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Neg for Node {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        match self {
+            Self::Const(c) => Self::Const(-c),
+            x => Self::Neg(Box::new(x)),
+        }
+    }
+}
+
+macro_rules! impl_node_op {
+    ($op:ident => $f:ident) => {
+        impl $op for Node {
+            type Output = Self;
+
+            #[inline]
+            fn $f(self, rhs: Self) -> Self::Output {
+                Self::binary(Op::$op, self, rhs)
+            }
+        }
+    };
+}
+
+impl_node_op!(Add => add);
+impl_node_op!(Sub => sub);
+impl_node_op!(Mul => mul);
+impl_node_op!(Div => div);
+impl_node_op!(Rem => rem);
+
+impl Elementary for Node {
+    #[inline]
+    fn pow(self, exp: Self) -> Result<Self, ErrorKind> {
+        Ok(Self::binary(Op::Pow, self, exp))
+    }
+
+    fn call(func: Func, args: Vec<Self>) -> Result<Self, ErrorKind> {
+        let consts = args
+            .iter()
+            .map(|arg| match arg {
+                Self::Const(c) => Some(*c),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+
+        match consts {
+            Some(consts) => f64::call(func, consts).map(Self::Const),
+            None => Ok(Self::Call(func, args)),
+        }
+    }
+}
+
+impl FromLiteral for Node {
+    #[inline]
+    fn from_literal(literal: &str) -> Result<Self, ErrorKind> {
+        f64::from_literal(literal).map(Self::Const)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rstest::*;
 
+    use std::time::Duration;
+
     // Wrap custom message to reduce repetition
     macro_rules! assert_expr_eq {
         ($expr: expr, $expect: expr) => {
@@ -419,13 +1235,243 @@ mod tests {
     }
 
     #[rstest]
-    #[case::parenthesis_right("1 + (1 / 2", Error::MismatchedParentheses("1 + (1 / 2".to_string()))]
-    #[case::parenthesis_left("1 + 1 / 2)", Error::MismatchedParentheses("1 + 1 / 2)".to_string()))]
-    #[case::value_separateor("1 2", Error::InvalidValueSeparation(1.0, 2.0))]
-    #[case::missing_neg_arg("-", Error::MissingArguments)]
-    #[case::missing_op_arg("1 +", Error::MissingArguments)]
-    fn failures(#[case] expr: &str, #[case] expected: Error) {
-        let error = expr.eval::<f64>().expect_err("eval should fail");
-        assert_eq!(error, expected);
+    #[case("2 ^ 3", 8.0)]
+    #[case("2 ^ 3 ^ 2", 512.0)]
+    #[case("(2 ^ 3) ^ 2", 64.0)]
+    #[case("-2 ^ 2", -4.0)]
+    #[case("2 ^ -1", 0.5)]
+    #[case("2 * 3 ^ 2", 18.0)]
+    #[case("7 % 3", 1.0)]
+    #[case("-7 % 3", -1.0)]
+    #[case("1 + 10 % 4 * 2", 5.0)]
+    fn exponentiation_and_modulo(#[case] expr: &str, #[case] expected: f64) {
+        assert_expr_eq!(expr, expected);
+    }
+
+    #[rstest]
+    #[case("sqrt(16)", 4.0)]
+    #[case("abs(-3) + 1", 4.0)]
+    #[case("sin(0)", 0.0)]
+    #[case("cos(0) * 2", 2.0)]
+    #[case("-exp(0)", -1.0)]
+    #[case("ln(exp(2))", 2.0)]
+    #[case("log(8, 2)", 3.0)]
+    #[case("max(1, 5, 3)", 5.0)]
+    #[case("min(4)", 4.0)]
+    #[case("max(1, -2 * 3, min(7, 2 + 3))", 5.0)]
+    #[case("sqrt(3 ^ 2 + 4 ^ 2)", 5.0)]
+    #[case("2 ^ sqrt(4) ^ 2", 16.0)]
+    fn functions(#[case] expr: &str, #[case] expected: f64) {
+        assert_expr_eq!(expr, expected);
+    }
+
+    #[rstest]
+    #[case::parenthesis_right("1 + (1 / 2", ErrorKind::MismatchedParentheses, 4..5)]
+    #[case::parenthesis_left("1 + 1 / 2)", ErrorKind::MismatchedParentheses, 9..10)]
+    #[case::nested_parenthesis("((1) + 2", ErrorKind::MismatchedParentheses, 0..1)]
+    #[case::value_separateor("1 2", ErrorKind::InvalidValueSeparation, 0..3)]
+    #[case::value_separateor_decimal("1 + 2.5  3", ErrorKind::InvalidValueSeparation, 4..10)]
+    #[case::missing_neg_arg("-", ErrorKind::MissingArguments, 0..1)]
+    #[case::missing_op_arg("1 +", ErrorKind::MissingArguments, 2..3)]
+    #[case::missing_pow_arg("2 ^", ErrorKind::MissingArguments, 2..3)]
+    #[case::unknown_func("1 + foo(1)", ErrorKind::UnknownToken("foo".to_string()), 4..7)]
+    #[case::unknown_char("1 $ 2", ErrorKind::UnknownToken("$".to_string()), 2..3)]
+    #[case::missing_call("sin 1", ErrorKind::MissingCallParenthesis(Func::Sin), 0..3)]
+    #[case::missing_call_end("1 + sqrt", ErrorKind::MissingCallParenthesis(Func::Sqrt), 4..8)]
+    #[case::too_many_args("2 * sin(1, 2)", ErrorKind::Arity { func: Func::Sin, expected: Arity::Exactly(1), actual: 2 }, 4..7)]
+    #[case::too_few_args("log(8)", ErrorKind::Arity { func: Func::Log, expected: Arity::Exactly(2), actual: 1 }, 0..3)]
+    #[case::no_args("max()", ErrorKind::Arity { func: Func::Max, expected: Arity::AtLeast(1), actual: 0 }, 0..3)]
+    #[case::empty_arg("max(1, , 2)", ErrorKind::MissingArguments, 3..11)]
+    #[case::separator("(1, 2)", ErrorKind::MisplacedSeparator, 0..6)]
+    #[case::top_level_separator("1, 2", ErrorKind::MisplacedSeparator, 1..2)]
+    #[case::empty_parentheses("1 + ()", ErrorKind::MissingArguments, 4..6)]
+    #[case::empty("  ", ErrorKind::EmptyExpression, 0..2)]
+    #[case::value_after_group("(1)(2)", ErrorKind::InvalidValueSeparation, 2..4)]
+    #[case::call_after_value("2 sin(1)", ErrorKind::InvalidValueSeparation, 0..5)]
+    #[case::unknown_var("1 + x", ErrorKind::UnknownToken("x".to_string()), 4..5)]
+    fn failures(#[case] expr: &str, #[case] kind: ErrorKind, #[case] span: Span) {
+        let error = try_calc(expr).expect_err("eval should fail");
+        assert_eq!(error, Error { kind, span });
+    }
+
+    #[test]
+    fn error_message() {
+        let error = try_calc("2 * (1 + 3").expect_err("eval should fail");
+        assert_eq!(error.to_string(), "Mismatched parenthesis at 4..5");
+    }
+
+    fn ratio(numer: i64, denom: i64) -> BigRational {
+        BigRational::new(numer.into(), denom.into())
+    }
+
+    #[rstest]
+    #[case("0.1 + 0.2", ratio(3, 10))]
+    #[case("1 / 3 + 1 / 6", ratio(1, 2))]
+    #[case("2 ^ -2", ratio(1, 4))]
+    #[case("(2 / 3) ^ 3", ratio(8, 27))]
+    #[case("7.5 % 2", ratio(3, 2))]
+    #[case("max(0.1, 1 / 3, -2)", ratio(1, 3))]
+    #[case("abs(-0.125)", ratio(1, 8))]
+    fn rational(#[case] expr: &str, #[case] expected: BigRational) {
+        assert_eq!(calc_rational(expr), Ok(expected));
+    }
+
+    #[rstest]
+    #[case("7 / 2", 3.into())]
+    #[case("-7 % 3", (-1).into())]
+    #[case("2 ^ 100", "1267650600228229401496703205376".parse().unwrap())]
+    #[case("min(3, -4) * 5", (-20).into())]
+    #[case("1 ^ 1000000000", 1.into())]
+    #[case("0 ^ 1000000000", 0.into())]
+    fn integer(#[case] expr: &str, #[case] expected: BigInt) {
+        assert_eq!(calc_integer(expr), Ok(expected));
+    }
+
+    #[rstest]
+    #[case::div_by_zero("1 / (2 - 2)", ErrorKind::DivisionByZero, 2..3)]
+    #[case::rem_by_zero("1 % 0", ErrorKind::DivisionByZero, 2..3)]
+    #[case::zero_inverse("0 ^ -1", ErrorKind::DivisionByZero, 2..3)]
+    #[case::irrational_pow("2 ^ 0.5", ErrorKind::Unsupported("non-integral exponent '1/2'".to_string()), 2..3)]
+    #[case::transcendental("sin(1)", ErrorKind::Unsupported("'sin' of an exact number".to_string()), 0..3)]
+    #[case::huge_inverse("0.5 ^ -1000000000", ErrorKind::ExponentTooLarge("1000000000".to_string()), 4..5)]
+    #[case::huge_exponent("2 ^ 100000000000000000000", ErrorKind::ExponentTooLarge("100000000000000000000".to_string()), 2..3)]
+    #[timeout(Duration::from_secs(1))]
+    fn rational_failures(#[case] expr: &str, #[case] kind: ErrorKind, #[case] span: Span) {
+        assert_eq!(calc_rational(expr), Err(Error { kind, span }));
+    }
+
+    #[rstest]
+    #[case::div_by_zero("1 / 0", ErrorKind::DivisionByZero, 2..3)]
+    #[case::decimal("1.5 * 2", ErrorKind::ParseFailure("1.5".to_string()), 0..3)]
+    #[case::negative_pow("2 ^ -1", ErrorKind::Unsupported("integer exponent '-1'".to_string()), 2..3)]
+    #[case::huge_pow("2 ^ 1000000000", ErrorKind::ExponentTooLarge("1000000000".to_string()), 2..3)]
+    #[case::over_limit("3 ^ 1048576", ErrorKind::ExponentTooLarge("1048576".to_string()), 2..3)]
+    #[case::huge_exponent("1 ^ 100000000000000000000", ErrorKind::ExponentTooLarge("100000000000000000000".to_string()), 2..3)]
+    #[case::huge_base("(10 ^ 1000) ^ 1000", ErrorKind::ExponentTooLarge("1000".to_string()), 12..13)]
+    #[timeout(Duration::from_secs(1))]
+    fn integer_failures(#[case] expr: &str, #[case] kind: ErrorKind, #[case] span: Span) {
+        assert_eq!(calc_integer(expr), Err(Error { kind, span }));
+    }
+
+    #[test]
+    fn interval() {
+        let vars = [
+            ("x", Interval::new(1.0, 2.0)),
+            ("y", Interval::around(10.0, 0.5)),
+        ];
+
+        let actual = calc_interval("x * y - x ^ 2", &vars).expect("valid expression");
+        assert_eq!(Interval::new(5.5, 20.0), actual);
+
+        let actual = calc_interval("max(x, y / 10) % 1", &vars).expect("valid expression");
+        assert_eq!(Interval::new(0.0, 1.0), actual);
+    }
+
+    #[test]
+    fn interval_literals() {
+        let actual = calc_interval("0.1 + 0.2", &[]).expect("valid expression");
+        assert!(actual.contains(0.1 + 0.2) && actual.contains(0.3));
+        assert!(actual.width() < 1e-15);
+    }
+
+    #[rstest]
+    #[case("0.25", true)]
+    #[case("1.5", true)]
+    #[case("3", true)]
+    #[case("1152921504606846976", true)]
+    #[case("9007199254740993", false)]
+    #[case("0.1", false)]
+    #[case("123.456", false)]
+    #[trace]
+    fn exact_interval_literals(#[case] literal: &str, #[case] point: bool) {
+        let x = Interval::from_literal(literal).unwrap();
+        assert_eq!(x.is_point(), point, "{x}");
+
+        let exact = BigRational::from_literal(literal).unwrap();
+        assert!(BigRational::from_float(x.lo()).unwrap() <= exact, "{x}");
+        assert!(BigRational::from_float(x.hi()).unwrap() >= exact, "{x}");
+    }
+
+    #[rstest]
+    #[case::zero_lo("1 / x", Interval::new(0.0, 2.0), Interval::new(0.5, f64::INFINITY))]
+    #[case::zero_inside("1 / x", Interval::new(-1.0, 2.0), Interval::ENTIRE)]
+    #[case::pole("tan(x)", Interval::new(1.0, 2.0), Interval::ENTIRE)]
+    #[case::sqrt_domain("sqrt(x)", Interval::new(-1.0, 4.0), Interval::new(0.0, 2.0))]
+    fn interval_singularities(#[case] expr: &str, #[case] x: Interval, #[case] expected: Interval) {
+        assert_eq!(calc_interval(expr, &[("x", x)]), Ok(expected));
+    }
+
+    #[rstest]
+    #[case::div_by_zero("1 / (x - x)", ErrorKind::DivisionByZero, 2..3)]
+    #[case::domain("ln(x - 5)", ErrorKind::OutOfDomain("ln".to_string()), 0..2)]
+    #[case::pow_domain("(-x) ^ 0.5", ErrorKind::OutOfDomain("^".to_string()), 5..6)]
+    #[case::reserved("sin + 1", ErrorKind::MissingCallParenthesis(Func::Sin), 0..3)]
+    fn interval_failures(#[case] expr: &str, #[case] kind: ErrorKind, #[case] span: Span) {
+        let vars = [("x", Interval::point(2.0)), ("sin", Interval::point(1.0))];
+        assert_eq!(calc_interval(expr, &vars), Err(Error { kind, span }));
+    }
+
+    #[rstest]
+    #[case("2 /2+3 * 4.75- -6")]
+    #[case("((2.33 / (2.9+3.5)*4) - -6)")]
+    #[case("2 ^ 3 ^ 2 % 5")]
+    #[case("max(1, -2 * 3, min(7, 2 + 3)) + log(8, 2) - sqrt(abs(-16))")]
+    #[case("sin(1) * cos(2) / tan(3) - atan(0.5) + tanh(0.1)")]
+    fn compile_constants(#[case] expr: &str) {
+        let compiled = compile(expr, &[]).expect("valid expression");
+        assert_eq!(calc(expr), compiled.eval(&[]));
+    }
+
+    #[rstest]
+    #[case("x * y - x ^ 2", &[1.5, -2.0], -5.25)]
+    #[case("-(x + 1) * 2 ^ 3", &[0.5, 0.0], -12.0)]
+    #[case("max(x, y, 0) % 3", &[7.0, 2.0], 1.0)]
+    #[case("log(y, x) + ln(1)", &[2.0, 8.0], 3.0)]
+    fn compile_variables(#[case] expr: &str, #[case] args: &[f64], #[case] expected: f64) {
+        let compiled = compile(expr, &["x", "y"]).expect("valid expression");
+        assert_eq!(expected, compiled.eval(args));
+    }
+
+    #[test]
+    fn constant_folding() {
+        let node: Node = "2 * 3 + sqrt(4) * x - -(1)"
+            .eval_with(|name| {
+                if name == "x" {
+                    Some(Node::Var(0))
+                } else {
+                    None
+                }
+            })
+            .expect("valid expression");
+
+        let expected = Node::binary(
+            Op::Sub,
+            Node::binary(
+                Op::Add,
+                Node::Const(6.0),
+                Node::binary(Op::Mul, Node::Const(2.0), Node::Var(0)),
+            ),
+            Node::Const(-1.0),
+        );
+
+        assert_eq!(expected, node);
+    }
+
+    #[rstest]
+    #[case::unknown_var("x + z", ErrorKind::UnknownToken("z".to_string()), 4..5)]
+    #[case::parenthesis("(x", ErrorKind::MismatchedParentheses, 0..1)]
+    fn compile_failures(#[case] expr: &str, #[case] kind: ErrorKind, #[case] span: Span) {
+        let error = compile(expr, &["x"]).err().expect("compile should fail");
+        assert_eq!(Error { kind, span }, error);
+    }
+
+    #[rstest]
+    #[case(1_000_000)]
+    #[timeout(Duration::from_secs(2))]
+    #[trace]
+    fn bench_compiled(#[case] n: usize) {
+        let compiled = compile("x * sin(y) + (x - 1) ^ 2 / max(y, 1)", &["x", "y"]).unwrap();
+        let sum = (0..n).map(|i| compiled.eval(&[i as f64, 0.5])).sum::<f64>();
+        assert!(sum.is_finite());
     }
 }
@@ -0,0 +1,267 @@
+//! Conversions between calculator expressions and symbolic [Expr]essions of
+//! [differentiation](crate::differentiation)
+//!
+//! Infix strings are parsed by the very same shunting-yard evaluator as in [calc](super::calc),
+//! only evaluated into [Expr]s instead of numbers, so that they can be differentiated or otherwise
+//! manipulated symbolically. Conversely, an [Expr] is rendered in infix notation and compiled by
+//! [compile](super::compile) into a closure which evaluates it numerically.
+
+use super::{
+    compile, Compiled, Elementary, Error, ErrorKind, Eval, FromLiteral, Func, Op as CalcOp,
+};
+use crate::differentiation::{DiffError, Expr, Func as SymFunc, Op};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+/// Parses an infix expression (e.g. `2*sin(x)^2`) into a symbolic [Expr], where any identifier
+/// which does not name a function is a variable.
+///
+/// The result is the same as if the expression was parsed in
+/// [Infix](crate::differentiation::Notation::Infix) notation. Functions `min` and `max` and the
+/// remainder `%` have no symbolic counterpart and are reported as [ErrorKind::Unsupported].
+pub fn parse_symbolic(expr: &str) -> Result<Expr, Error> {
+    expr.eval_with(|name| Some(Symbolic(Expr::Var(name.to_owned()))))
+        .map(|Symbolic(expr)| expr)
+}
+
+/// Compiles a symbolic [Expr] with variables `vars` into a [Compiled] closure (see [compile]).
+///
+/// Note that errors are located in the infix rendering of `expr` and that function `sign` is not
+/// supported by the calculator.
+pub fn compile_symbolic(expr: &Expr, vars: &[&str]) -> Result<Compiled, Error> {
+    compile(&expr.infix().to_string(), vars)
+}
+
+/// Symbolic value of the shunting-yard evaluation
+struct Symbolic(Expr);
+
+impl Symbolic {
+    /// Creates a validated and simplified binary expression
+    fn binary(op: Op, lhs: Self, rhs: Self) -> Result<Self, ErrorKind> {
+        Ok(Self(Expr::binary(op, lhs.0, rhs.0)?))
+    }
+}
+
+/// Note: Only a division by zero has a direct counterpart among calculator errors
+impl From<DiffError> for ErrorKind {
+    fn from(e: DiffError) -> Self {
+        match e {
+            DiffError::DivisionByZero(_) => Self::DivisionByZero,
+            e => Self::Unsupported(e.to_string()),
+        }
+    }
+}
+
+impl Neg for Symbolic {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
+macro_rules! impl_symbolic_op {
+    ($op:ident => $f:ident) => {
+        impl $op for Symbolic {
+            type Output = Self;
+
+            #[inline]
+            fn $f(self, rhs: Self) -> Self::Output {
+                Self($op::$f(self.0, rhs.0))
+            }
+        }
+    };
+}
+
+impl_symbolic_op!(Add => add);
+impl_symbolic_op!(Sub => sub);
+impl_symbolic_op!(Mul => mul);
+
+/// Note: Evaluation divides by [Elementary::try_div], this impl is only required by [Apply]
+///
+/// [Apply]: super::Apply
+impl Div for Symbolic {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: Self) -> Self::Output {
+        self.try_div(rhs).expect("division by zero")
+    }
+}
+
+/// Note: Evaluation takes remainders by [Elementary::try_rem], this impl is only required by
+/// [Apply]
+///
+/// [Apply]: super::Apply
+impl Rem for Symbolic {
+    type Output = Self;
+
+    #[inline]
+    fn rem(self, rhs: Self) -> Self::Output {
+        self.try_rem(rhs)
+            .expect("remainder of symbolic expressions")
+    }
+}
+
+impl Elementary for Symbolic {
+    #[inline]
+    fn try_div(self, rhs: Self) -> Result<Self, ErrorKind> {
+        Self::binary(Op::Div, self, rhs)
+    }
+
+    #[inline]
+    fn try_rem(self, _: Self) -> Result<Self, ErrorKind> {
+        Err(ErrorKind::Unsupported(format!(
+            "'{}' of symbolic expressions",
+            CalcOp::Rem
+        )))
+    }
+
+    #[inline]
+    fn pow(self, exp: Self) -> Result<Self, ErrorKind> {
+        Self::binary(Op::Pow, self, exp)
+    }
+
+    fn call(func: Func, args: Vec<Self>) -> Result<Self, ErrorKind> {
+        let mut args = args.into_iter();
+        let x = args.next().ok_or(ErrorKind::MissingArguments)?;
+
+        let f = match func {
+            Func::Abs => SymFunc::Abs,
+            Func::Sqrt => SymFunc::Sqrt,
+            Func::Exp => SymFunc::Exp,
+            Func::Ln => SymFunc::Ln,
+            Func::Sin => SymFunc::Sin,
+            Func::Cos => SymFunc::Cos,
+            Func::Tan => SymFunc::Tan,
+            Func::Asin => SymFunc::Asin,
+            Func::Acos => SymFunc::Acos,
+            Func::Atan => SymFunc::Atan,
+            Func::Sinh => SymFunc::Sinh,
+            Func::Cosh => SymFunc::Cosh,
+            Func::Tanh => SymFunc::Tanh,
+            Func::Log => {
+                let base = args.next().ok_or(ErrorKind::MissingArguments)?;
+                return Self::binary(Op::Log, x, base);
+            }
+            Func::Min | Func::Max => {
+                return Err(ErrorKind::Unsupported(format!(
+                    "'{func}' of symbolic expressions"
+                )))
+            }
+        };
+
+        Ok(Self(Expr::unary(f, x.0)))
+    }
+}
+
+impl FromLiteral for Symbolic {
+    #[inline]
+    fn from_literal(literal: &str) -> Result<Self, ErrorKind> {
+        f64::from_literal(literal).map(|c| Self(Expr::Const(c)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::differentiation::Notation;
+    use crate::eval_expression::Span;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("42")]
+    #[case("x")]
+    #[case("-x^2 + 3 * x - 1")]
+    #[case("2 * sin(x)^2")]
+    #[case("(x + 1) / (x - 1)")]
+    #[case("2^-x * exp(-(x - 1))")]
+    #[case("log(x, 2) + ln(abs(x))")]
+    #[case("sqrt(tanh(x) * atan(y))")]
+    #[case("x^y^2 / 2.5")]
+    #[case("x * 1 - (y - y) + 0 * z")]
+    #[trace]
+    fn same_as_infix_notation(#[case] infix: &str) {
+        let expected = Notation::Infix.parse(infix).unwrap();
+        assert_eq!(
+            parse_symbolic(infix).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[rstest]
+    #[case("x^2 - 3 * x + 1")]
+    #[case("2 * sin(x)^2 + cos(3 * x)")]
+    #[case("(x + 1) / (x^2 + 1)")]
+    #[case("exp(-x / 2) * ln(x)")]
+    #[case("log(x, 10) - sqrt(x)")]
+    #[case("atan(x) + sinh(x) - tanh(x)")]
+    #[trace]
+    fn round_trip(#[case] infix: &str) {
+        let expected = compile(infix, &["x"]).unwrap();
+        let actual = compile_symbolic(&parse_symbolic(infix).unwrap(), &["x"]).unwrap();
+
+        for x in [0.25, 1.0, 2.5, 7.0] {
+            let (expected, actual) = (expected.eval(&[x]), actual.eval(&[x]));
+            assert!(
+                (actual - expected).abs() < 1e-12 * expected.abs().max(1.0),
+                "{actual} != {expected}"
+            );
+        }
+    }
+
+    #[rstest]
+    #[case("x^3 - 2 * x", "3 * x^2 - 2")]
+    #[case("sin(x) * cos(x)", "cos(x)^2 - sin(x)^2")]
+    #[case("exp(2 * x) / x", "(2 * x - 1) * exp(2 * x) / x^2")]
+    #[case("log(x, 2)", "1 / (x * ln(2))")]
+    #[trace]
+    fn derivatives(#[case] infix: &str, #[case] derivative: &str) {
+        let df = parse_symbolic(infix).unwrap().derivative("x").unwrap();
+        let actual = compile_symbolic(&df, &["x"]).unwrap();
+        let expected = compile(derivative, &["x"]).unwrap();
+
+        for x in [0.5, 1.0, 3.0] {
+            let (expected, actual) = (expected.eval(&[x]), actual.eval(&[x]));
+            assert!(
+                (actual - expected).abs() < 1e-9 * expected.abs().max(1.0),
+                "{actual} != {expected}"
+            );
+        }
+    }
+
+    #[rstest]
+    #[case("x % 2", ErrorKind::Unsupported("'%' of symbolic expressions".to_string()), 2..3)]
+    #[case(
+        "max(x, 1)",
+        ErrorKind::Unsupported("'max' of symbolic expressions".to_string()),
+        0..3
+    )]
+    #[case("1 + x / (x - x)", ErrorKind::DivisionByZero, 6..7)]
+    #[case("x +", ErrorKind::MissingArguments, 2..3)]
+    #[trace]
+    fn parse_failures(#[case] infix: &str, #[case] kind: ErrorKind, #[case] span: Span) {
+        assert_eq!(parse_symbolic(infix).unwrap_err(), Error { kind, span });
+    }
+
+    #[test]
+    fn diff_errors() {
+        let error = DiffError::DivisionByZero("(/ x 0)".to_string());
+        assert_eq!(ErrorKind::from(error), ErrorKind::DivisionByZero);
+
+        let error = DiffError::UnsupportedForm("(^ -2 x)".to_string());
+        assert_eq!(
+            ErrorKind::from(error),
+            ErrorKind::Unsupported("Unsupported derivative form '(^ -2 x)'".to_string())
+        );
+    }
+
+    #[test]
+    fn unsupported_function() {
+        let expr = Notation::Prefix.parse("(sign x)").unwrap();
+        assert_eq!(
+            compile_symbolic(&expr, &["x"]).err().map(|e| e.kind),
+            Some(ErrorKind::UnknownToken("sign".to_string()))
+        );
+    }
+}
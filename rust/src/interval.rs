@@ -0,0 +1,692 @@
+use std::{
+    f64::consts::{FRAC_PI_2, PI, TAU},
+    fmt::Display,
+    ops::{Add, Div, Mul, Neg, Rem, Sub},
+};
+
+/// Closed interval `[lo, hi]` of real numbers (possibly unbounded) used for
+/// [interval arithmetic](https://en.wikipedia.org/wiki/Interval_arithmetic).
+///
+/// All operations produce an interval which *encloses* every possible result of applying the
+/// operation to members of the operands. To keep this guarantee under floating point rounding,
+///  - arithmetic rounds the bounds outwards exactly when the result is not representable (using
+///    error-free transformations), so e.g. integral bounds stay exact
+///  - elementary functions widen the bounds by one ulp as these are not correctly rounded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    lo: f64,
+    hi: f64,
+}
+
+impl Interval {
+    /// The whole real line `[-inf, inf]`
+    pub const ENTIRE: Self = Self {
+        lo: f64::NEG_INFINITY,
+        hi: f64::INFINITY,
+    };
+
+    /// Creates new interval `[lo, hi]`
+    ///
+    /// # Panics
+    /// Panics if `lo > hi` or any of the bounds is `NaN`
+    pub fn new(lo: f64, hi: f64) -> Self {
+        assert!(lo <= hi, "invalid interval bounds [{lo}, {hi}]");
+        Self { lo, hi }
+    }
+
+    /// Creates a degenerate interval `[x, x]`
+    #[inline]
+    pub fn point(x: f64) -> Self {
+        Self::new(x, x)
+    }
+
+    /// Creates the interval between the neighbours of `x`, which encloses any real number that
+    /// rounds to `x` (e.g. the real `0.1` which has no exact [f64] representation)
+    #[inline]
+    pub fn enclosing(x: f64) -> Self {
+        Self::new(next_down(x), next_up(x))
+    }
+
+    /// Creates an interval `[x - r, x + r]` of a measurement `x` with uncertainty `r >= 0`
+    pub fn around(x: f64, r: f64) -> Self {
+        assert!(r >= 0.0, "uncertainty must be non-negative, got {r}");
+        let (lo, e_lo) = two_sum(x, -r);
+        let (hi, e_hi) = two_sum(x, r);
+        Self::new(round_down(lo, e_lo), round_up(hi, e_hi))
+    }
+
+    #[inline]
+    pub fn lo(&self) -> f64 {
+        self.lo
+    }
+
+    #[inline]
+    pub fn hi(&self) -> f64 {
+        self.hi
+    }
+
+    #[inline]
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    #[inline]
+    pub fn mid(&self) -> f64 {
+        self.lo + self.width() / 2.0
+    }
+
+    #[inline]
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    #[inline]
+    pub fn is_point(&self) -> bool {
+        self.lo == self.hi
+    }
+
+    /// Returns the smallest interval containing both `self` and `other`
+    #[inline]
+    pub fn hull(self, other: Self) -> Self {
+        Self::new(self.lo.min(other.lo), self.hi.max(other.hi))
+    }
+
+    /// Division which returns `None` iff `rhs` is `[0, 0]`.
+    ///
+    /// If `rhs` contains zero then the (mathematical) result might be a union of two disjoint
+    /// intervals, in which case their [hull](Self::hull) is returned.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        let Self { lo: a, hi: b } = self;
+        let Self { lo: c, hi: d } = rhs;
+
+        if c == 0.0 && d == 0.0 {
+            return None;
+        }
+
+        // Zero is not in the divisor, so divide by each endpoint
+        if c > 0.0 || d < 0.0 {
+            let lo = div_down(a, c).min(div_down(a, d)).min(div_down(b, c));
+            let lo = lo.min(div_down(b, d));
+            let hi = div_up(a, c).max(div_up(a, d)).max(div_up(b, c));
+            let hi = hi.max(div_up(b, d));
+            return Some(Self::new(lo, hi));
+        }
+
+        // The divisor contains zero as one of its endpoints and the dividend is strictly one-sided
+        let div = match (a > 0.0, b < 0.0) {
+            (true, _) if c == 0.0 => Self::new(div_down(a, d), f64::INFINITY),
+            (true, _) if d == 0.0 => Self::new(f64::NEG_INFINITY, div_up(a, c)),
+            (_, true) if c == 0.0 => Self::new(f64::NEG_INFINITY, div_up(b, d)),
+            (_, true) if d == 0.0 => Self::new(div_down(b, c), f64::INFINITY),
+            // Either the dividend contains zero or the result is a union of two half-lines
+            _ => Self::ENTIRE,
+        };
+
+        Some(div)
+    }
+
+    /// Remainder which returns `None` iff `rhs` is `[0, 0]`.
+    ///
+    /// Except for finite point operands, the result is a conservative enclosure based on
+    /// `|x % y| < |y|` and `|x % y| <= |x|`.
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        if rhs.lo == 0.0 && rhs.hi == 0.0 {
+            return None;
+        }
+
+        if self.is_point() && rhs.is_point() && self.lo.is_finite() {
+            return Some(Self::point(self.lo % rhs.lo));
+        }
+
+        let m = rhs.lo.abs().max(rhs.hi.abs());
+
+        let lo = if self.lo >= 0.0 { 0.0 } else { self.lo.max(-m) };
+        let hi = if self.hi <= 0.0 { 0.0 } else { self.hi.min(m) };
+
+        Some(Self::new(lo, hi))
+    }
+
+    /// Raises `self` to the power of `exp`, returning `None` if `self` lies completely outside of
+    /// the domain of the power (e.g. a negative base with non-integral exponent).
+    ///
+    /// Integral point exponents are handled exactly for any base, otherwise the power is computed
+    /// as `exp(exp * ln(self))` on the non-negative part of the base.
+    pub fn pow(self, exp: Self) -> Option<Self> {
+        if exp.is_point() && exp.lo.fract() == 0.0 && exp.lo.abs() <= i32::MAX as f64 {
+            return self.powi(exp.lo as i32);
+        }
+
+        if self.hi < 0.0 {
+            return None;
+        }
+
+        let base = Self::new(self.lo.max(0.0), self.hi);
+
+        if base.hi == 0.0 {
+            return if exp.lo > 0.0 {
+                Some(Self::point(0.0))
+            } else {
+                None
+            };
+        }
+
+        Some((exp * base.ln()?).exp())
+    }
+
+    /// Raises `self` to an integral power `n`, which is `None` only for `[0, 0]^n` with `n < 0`
+    pub fn powi(self, n: i32) -> Option<Self> {
+        let pow = self.powu(n.unsigned_abs());
+        if n < 0 {
+            Self::point(1.0).checked_div(pow)
+        } else {
+            Some(pow)
+        }
+    }
+
+    /// Raises `self` to a non-negative integral power `n`
+    fn powu(self, n: u32) -> Self {
+        let Self { lo, hi } = self;
+
+        if n == 0 {
+            Self::point(1.0)
+        } else if lo >= 0.0 {
+            Self::new(pow_down(lo, n), pow_up(hi, n))
+        } else if n % 2 == 1 {
+            // Odd powers are monotone, negative bounds are rounded via the odd symmetry
+            let lo = -pow_up(-lo, n);
+            let hi = if hi < 0.0 {
+                -pow_down(-hi, n)
+            } else {
+                pow_up(hi, n)
+            };
+            Self::new(lo, hi)
+        } else if hi <= 0.0 {
+            Self::new(pow_down(-hi, n), pow_up(-lo, n))
+        } else {
+            Self::new(0.0, pow_up(-lo, n).max(pow_up(hi, n)))
+        }
+    }
+
+    pub fn abs(self) -> Self {
+        if self.lo >= 0.0 {
+            self
+        } else if self.hi <= 0.0 {
+            -self
+        } else {
+            Self::new(0.0, (-self.lo).max(self.hi))
+        }
+    }
+
+    /// Returns `None` if `self` is strictly negative, otherwise the square root of the
+    /// non-negative part of `self`
+    pub fn sqrt(self) -> Option<Self> {
+        if self.hi < 0.0 {
+            return None;
+        }
+        let lo = self.lo.max(0.0);
+        Some(Self::new(sqrt_down(lo), sqrt_up(self.hi)))
+    }
+
+    pub fn exp(self) -> Self {
+        self.monotone(f64::exp).clamp(0.0, f64::INFINITY)
+    }
+
+    /// Returns `None` if `self` is non-positive, otherwise the logarithm of the positive part of
+    /// `self`
+    pub fn ln(self) -> Option<Self> {
+        if self.hi <= 0.0 {
+            return None;
+        }
+        Some(Self::new(self.lo.max(0.0), self.hi).monotone(f64::ln))
+    }
+
+    pub fn sin(self) -> Self {
+        // sin(x) = cos(x - pi/2), so the extremes lie at pi/2 + 2k*pi (max) and -pi/2 + 2k*pi
+        self.periodic(f64::sin, FRAC_PI_2, -FRAC_PI_2)
+    }
+
+    pub fn cos(self) -> Self {
+        self.periodic(f64::cos, 0.0, PI)
+    }
+
+    pub fn tan(self) -> Self {
+        // tan is increasing between consecutive poles at pi/2 + k*pi
+        let k = ((self.lo - FRAC_PI_2) / PI).ceil();
+        if self.width() >= PI || FRAC_PI_2 + k * PI <= self.hi {
+            return Self::ENTIRE;
+        }
+        self.monotone(f64::tan)
+    }
+
+    /// Returns `None` if `self` lies outside of `[-1, 1]`, otherwise the arcsine of the part of
+    /// `self` within this domain
+    pub fn asin(self) -> Option<Self> {
+        self.restrict(-1.0, 1.0)
+            .map(|x| x.monotone(f64::asin).clamp(-FRAC_PI_2, FRAC_PI_2))
+    }
+
+    /// Returns `None` if `self` lies outside of `[-1, 1]`, otherwise the arccosine of the part of
+    /// `self` within this domain
+    pub fn acos(self) -> Option<Self> {
+        self.restrict(-1.0, 1.0)
+            .map(|x| (-x).monotone(|x| (-x).acos()).clamp(0.0, PI))
+    }
+
+    pub fn atan(self) -> Self {
+        self.monotone(f64::atan).clamp(-FRAC_PI_2, FRAC_PI_2)
+    }
+
+    pub fn sinh(self) -> Self {
+        self.monotone(f64::sinh)
+    }
+
+    pub fn cosh(self) -> Self {
+        self.abs().monotone(f64::cosh).clamp(1.0, f64::INFINITY)
+    }
+
+    pub fn tanh(self) -> Self {
+        self.monotone(f64::tanh).clamp(-1.0, 1.0)
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        Self::new(self.lo.min(other.lo), self.hi.min(other.hi))
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Self::new(self.lo.max(other.lo), self.hi.max(other.hi))
+    }
+
+    /// Applies a non-decreasing function `f` to the bounds and widens the result by one ulp
+    #[inline]
+    fn monotone(self, f: impl Fn(f64) -> f64) -> Self {
+        Self::new(next_down(f(self.lo)), next_up(f(self.hi)))
+    }
+
+    /// Applies a `2pi`-periodic function `f` bounded by `[-1, 1]` which attains its maximum at
+    /// `max + 2k*pi` and minimum at `min + 2k*pi`
+    fn periodic(self, f: impl Fn(f64) -> f64, max: f64, min: f64) -> Self {
+        if self.width() >= TAU {
+            return Self::new(-1.0, 1.0);
+        }
+
+        // Checks whether there is `k` such that `x + 2k*pi` lies in `self`
+        let hits = |x: f64| x + ((self.lo - x) / TAU).ceil() * TAU <= self.hi;
+
+        let (a, b) = (f(self.lo), f(self.hi));
+        let lo = if hits(min) { -1.0 } else { next_down(a.min(b)) };
+        let hi = if hits(max) { 1.0 } else { next_up(a.max(b)) };
+
+        Self::new(lo, hi).clamp(-1.0, 1.0)
+    }
+
+    /// Intersects `self` with `[lo, hi]`, returning `None` if the intersection is empty
+    fn restrict(self, lo: f64, hi: f64) -> Option<Self> {
+        if self.hi < lo || self.lo > hi {
+            None
+        } else {
+            Some(Self::new(self.lo.max(lo), self.hi.min(hi)))
+        }
+    }
+
+    /// Clamps the bounds to `[lo, hi]` which is assumed to be a superset of the true range
+    #[inline]
+    fn clamp(self, lo: f64, hi: f64) -> Self {
+        Self::new(self.lo.clamp(lo, hi), self.hi.clamp(lo, hi))
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
+
+impl From<f64> for Interval {
+    #[inline]
+    fn from(x: f64) -> Self {
+        Self::point(x)
+    }
+}
+
+impl Neg for Interval {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Self::new(-self.hi, -self.lo)
+    }
+}
+
+impl Add for Interval {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let (lo, e_lo) = two_sum(self.lo, rhs.lo);
+        let (hi, e_hi) = two_sum(self.hi, rhs.hi);
+        Self::new(round_down(lo, e_lo), round_up(hi, e_hi))
+    }
+}
+
+impl Sub for Interval {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + (-rhs)
+    }
+}
+
+impl Mul for Interval {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let (a, b, c, d) = (self.lo, self.hi, rhs.lo, rhs.hi);
+        let lo = mul_down(a, c).min(mul_down(a, d)).min(mul_down(b, c));
+        let lo = lo.min(mul_down(b, d));
+        let hi = mul_up(a, c).max(mul_up(a, d)).max(mul_up(b, c));
+        let hi = hi.max(mul_up(b, d));
+        Self::new(lo, hi)
+    }
+}
+
+impl Div for Interval {
+    type Output = Self;
+
+    /// # Panics
+    /// Panics if `rhs` is `[0, 0]`, see [checked_div](Self::checked_div)
+    #[inline]
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(rhs).expect("interval division by zero")
+    }
+}
+
+/// Remainder with the semantics of [f64] remainder (i.e. with the sign of the dividend)
+impl Rem for Interval {
+    type Output = Self;
+
+    /// # Panics
+    /// Panics if `rhs` is `[0, 0]`, see [checked_rem](Self::checked_rem)
+    #[inline]
+    fn rem(self, rhs: Self) -> Self::Output {
+        self.checked_rem(rhs).expect("interval remainder by zero")
+    }
+}
+
+// Rounding utilities
+
+/// Returns the least [f64] greater than `x`
+fn next_up(x: f64) -> f64 {
+    if x.is_nan() || x == f64::INFINITY {
+        return x;
+    }
+    if x == 0.0 {
+        // smallest positive subnormal number
+        return f64::from_bits(1);
+    }
+    let bits = x.to_bits();
+    f64::from_bits(if x > 0.0 { bits + 1 } else { bits - 1 })
+}
+
+/// Returns the greatest [f64] less than `x`
+#[inline]
+fn next_down(x: f64) -> f64 {
+    -next_up(-x)
+}
+
+/// Given a rounded result `r` and its rounding error `e` (so that the exact result is `r + e`),
+/// returns the greatest [f64] not greater than the exact result
+#[inline]
+fn round_down(r: f64, e: f64) -> f64 {
+    if e < 0.0 {
+        next_down(r)
+    } else {
+        r
+    }
+}
+
+/// Given a rounded result `r` and its rounding error `e` (so that the exact result is `r + e`),
+/// returns the least [f64] not less than the exact result
+#[inline]
+fn round_up(r: f64, e: f64) -> f64 {
+    if e > 0.0 {
+        next_up(r)
+    } else {
+        r
+    }
+}
+
+/// Knuth's error-free *TwoSum* transformation: returns `(s, e)` such that `s = fl(a + b)` and
+/// `a + b = s + e` exactly
+#[inline]
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// Product of interval bounds with the convention that `0 * inf = 0` and its rounding error
+#[inline]
+fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    if a == 0.0 || b == 0.0 {
+        return (0.0, 0.0);
+    }
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+#[inline]
+fn mul_down(a: f64, b: f64) -> f64 {
+    let (p, e) = two_prod(a, b);
+    round_down(p, e)
+}
+
+#[inline]
+fn mul_up(a: f64, b: f64) -> f64 {
+    let (p, e) = two_prod(a, b);
+    round_up(p, e)
+}
+
+/// Quotient `a / b` (for `b != 0`) and the sign of its rounding error
+#[inline]
+fn two_div(a: f64, b: f64) -> (f64, f64) {
+    let q = a / b;
+    // a - q*b is exact, so the exact quotient is q + (a - q*b) / b
+    let r = (-q).mul_add(b, a);
+    (q, r * b.signum())
+}
+
+#[inline]
+fn div_down(a: f64, b: f64) -> f64 {
+    let (q, e) = two_div(a, b);
+    round_down(q, e)
+}
+
+#[inline]
+fn div_up(a: f64, b: f64) -> f64 {
+    let (q, e) = two_div(a, b);
+    round_up(q, e)
+}
+
+#[inline]
+fn sqrt_down(x: f64) -> f64 {
+    let r = x.sqrt();
+    round_down(r, (-r).mul_add(r, x))
+}
+
+#[inline]
+fn sqrt_up(x: f64) -> f64 {
+    let r = x.sqrt();
+    round_up(r, (-r).mul_add(r, x))
+}
+
+/// Lower bound on `x^n` for `x >= 0` and `n > 0`
+#[inline]
+fn pow_down(x: f64, n: u32) -> f64 {
+    pow_rounded(x, n, mul_down)
+}
+
+/// Upper bound on `x^n` for `x >= 0` and `n > 0`
+#[inline]
+fn pow_up(x: f64, n: u32) -> f64 {
+    pow_rounded(x, n, mul_up)
+}
+
+/// Exponentiation by squaring where each product is rounded by `mul`, which is monotone on
+/// non-negative operands and hence keeps the rounding direction of the result
+fn pow_rounded(mut x: f64, mut n: u32, mul: fn(f64, f64) -> f64) -> f64 {
+    let mut pow = 1.0;
+    while n > 0 {
+        if n % 2 == 1 {
+            pow = mul(pow, x);
+        }
+        n /= 2;
+        if n > 0 {
+            x = mul(x, x);
+        }
+    }
+    pow
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn iv(lo: f64, hi: f64) -> Interval {
+        Interval::new(lo, hi)
+    }
+
+    #[rstest]
+    #[case(iv(1.0, 2.0) + iv(3.0, 4.0), iv(4.0, 6.0))]
+    #[case(iv(1.0, 2.0) - iv(3.0, 4.0), iv(-3.0, -1.0))]
+    #[case(iv(-1.0, 2.0) * iv(3.0, 4.0), iv(-4.0, 8.0))]
+    #[case(iv(-2.0, -1.0) * iv(-3.0, 4.0), iv(-8.0, 6.0))]
+    #[case(iv(1.0, 2.0) / iv(4.0, 8.0), iv(0.125, 0.5))]
+    #[case(-iv(1.0, 2.0), iv(-2.0, -1.0))]
+    #[case(iv(7.0, 7.0) % iv(3.0, 3.0), iv(1.0, 1.0))]
+    #[case(iv(-5.0, 10.0) % iv(2.0, 3.0), iv(-3.0, 3.0))]
+    #[trace]
+    fn exact_arithmetic(#[case] actual: Interval, #[case] expected: Interval) {
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn outward_rounding() {
+        let third = iv(1.0, 1.0) / iv(3.0, 3.0);
+        assert!(third.lo() < third.hi());
+        assert!(third.lo() <= 1.0 / 3.0 && 1.0 / 3.0 <= third.hi());
+
+        let sum = iv(0.1, 0.1) + iv(0.2, 0.2);
+        assert!(sum.lo() < sum.hi());
+        assert!(sum.contains(0.1 + 0.2));
+    }
+
+    #[rstest]
+    #[case::positive_zero_lo(iv(1.0, 2.0), iv(0.0, 4.0), Some(iv(0.25, f64::INFINITY)))]
+    #[case::positive_zero_hi(iv(1.0, 2.0), iv(-4.0, 0.0), Some(iv(f64::NEG_INFINITY, -0.25)))]
+    #[case::negative_zero_lo(iv(-2.0, -1.0), iv(0.0, 4.0), Some(iv(f64::NEG_INFINITY, -0.25)))]
+    #[case::negative_zero_hi(iv(-2.0, -1.0), iv(-4.0, 0.0), Some(iv(0.25, f64::INFINITY)))]
+    #[case::split(iv(1.0, 2.0), iv(-1.0, 1.0), Some(Interval::ENTIRE))]
+    #[case::zero_dividend(iv(-1.0, 2.0), iv(0.0, 1.0), Some(Interval::ENTIRE))]
+    #[case::zero(iv(1.0, 2.0), iv(0.0, 0.0), None)]
+    #[trace]
+    fn division_by_zero(
+        #[case] x: Interval,
+        #[case] y: Interval,
+        #[case] expected: Option<Interval>,
+    ) {
+        assert_eq!(expected, x.checked_div(y));
+    }
+
+    #[rstest]
+    #[case(iv(-2.0, 3.0), 2, Some(iv(0.0, 9.0)))]
+    #[case(iv(-2.0, 3.0), 3, Some(iv(-8.0, 27.0)))]
+    #[case(iv(-3.0, -2.0), 2, Some(iv(4.0, 9.0)))]
+    #[case(iv(2.0, 4.0), -1, Some(iv(0.25, 0.5)))]
+    #[case(iv(0.0, 0.0), -1, None)]
+    #[case(iv(5.0, 6.0), 0, Some(iv(1.0, 1.0)))]
+    #[trace]
+    fn integral_powers(#[case] x: Interval, #[case] n: i32, #[case] expected: Option<Interval>) {
+        assert_eq!(expected, x.powi(n));
+    }
+
+    #[rstest]
+    #[case(-1.0756316267026835, 3)]
+    #[case(-1.0002001, 3)]
+    #[case(-0.1, 5)]
+    #[case(1.0002001, 7)]
+    #[trace]
+    fn odd_powers_enclose_exact(#[case] x: f64, #[case] n: i32) {
+        use num::{pow, BigRational};
+
+        let exact = pow(BigRational::from_float(x).unwrap(), n as usize);
+        let pow = Interval::point(x).powi(n).unwrap();
+        assert!(BigRational::from_float(pow.lo()).unwrap() <= exact, "{pow}");
+        assert!(BigRational::from_float(pow.hi()).unwrap() >= exact, "{pow}");
+
+        let neg = Interval::point(-x).powi(n).unwrap();
+        assert_eq!(-pow, neg);
+    }
+
+    #[test]
+    fn extreme_powers() {
+        let tiny = iv(2.0, 3.0).powi(i32::MIN).unwrap();
+        assert!(tiny.lo() == 0.0 && tiny.hi() < 1e-300, "{tiny}");
+        assert_eq!(Some(iv(1.0, 1.0)), iv(-1.0, -1.0).powi(i32::MIN));
+        assert_eq!(Some(iv(-1.0, 1.0)), iv(-1.0, 1.0).powi(i32::MAX));
+        assert_eq!(None, iv(0.0, 0.0).powi(i32::MIN));
+    }
+
+    #[rstest]
+    #[case::zero(iv(1.0, 1.0), iv(0.0, 0.0), None)]
+    #[case::zero_range(iv(1.0, 2.0), iv(0.0, 0.0), None)]
+    #[case::infinite_point(iv(f64::INFINITY, f64::INFINITY), iv(3.0, 3.0), Some(iv(0.0, 3.0)))]
+    #[case::entire(Interval::ENTIRE, iv(-2.0, 1.0), Some(iv(-2.0, 2.0)))]
+    #[case::contains_zero(iv(1.0, 5.0), iv(0.0, 2.0), Some(iv(0.0, 2.0)))]
+    #[trace]
+    fn remainder(#[case] x: Interval, #[case] y: Interval, #[case] expected: Option<Interval>) {
+        assert_eq!(expected, x.checked_rem(y));
+    }
+
+    #[test]
+    fn real_powers() {
+        let pow = iv(4.0, 9.0).pow(iv(0.5, 0.5)).expect("in domain");
+        assert!(pow.contains(2.0) && pow.contains(3.0));
+        assert!(pow.lo() > 1.99 && pow.hi() < 3.01);
+
+        assert_eq!(None, iv(-2.0, -1.0).pow(iv(0.5, 0.5)));
+    }
+
+    #[rstest]
+    #[case::sin_max(iv(1.0, 2.0).sin(), 1.0f64.sin(), 1.0)]
+    #[case::sin_full(iv(0.0, 7.0).sin(), -1.0, 1.0)]
+    #[case::sin_min(iv(4.0, 5.0).sin(), -1.0, 4.0f64.sin())]
+    #[case::cos_max(iv(-1.0, 1.0).cos(), 1.0f64.cos(), 1.0)]
+    #[case::cos_min(iv(3.0, 4.0).cos(), -1.0, 4.0f64.cos())]
+    #[case::cosh(iv(-1.0, 2.0).cosh(), 1.0, 2.0f64.cosh())]
+    #[case::abs(iv(-3.0, 2.0).abs(), 0.0, 3.0)]
+    #[case::sqrt(iv(-4.0, 16.0).sqrt().unwrap(), 0.0, 4.0)]
+    #[case::ln(iv(1.0, 1.0).ln().unwrap(), 0.0, 0.0)]
+    #[case::exp(iv(0.0, 1.0).exp(), 1.0, 1.0f64.exp())]
+    #[trace]
+    fn functions(#[case] actual: Interval, #[case] lo: f64, #[case] hi: f64) {
+        // bounds might be widened by an ulp
+        assert!(actual.lo() <= lo && lo - actual.lo() < 1e-15, "{actual}");
+        assert!(actual.hi() >= hi && actual.hi() - hi < 1e-15, "{actual}");
+    }
+
+    #[test]
+    fn poles() {
+        assert_eq!(Interval::ENTIRE, iv(1.0, 2.0).tan());
+        assert_eq!(Interval::ENTIRE, iv(-4.0, -1.0).tan());
+        assert!(iv(-1.0, 1.0).tan().contains(1.0f64.tan()));
+    }
+
+    #[test]
+    fn domain_errors() {
+        assert_eq!(None, iv(-2.0, -1.0).sqrt());
+        assert_eq!(None, iv(-2.0, 0.0).ln());
+        assert_eq!(None, iv(1.5, 2.0).asin());
+        assert_eq!(None, iv(-3.0, -2.0).acos());
+    }
+}
@@ -14,10 +14,12 @@ pub mod dubstep;
 pub mod eval_expression;
 pub mod in_array;
 pub mod integer_partitions;
+pub mod interval;
 pub mod isomorphism;
 pub mod magnet_particles;
 pub mod molecule;
 pub mod move_zeros;
+pub mod number_theory;
 pub mod parts_sums;
 pub mod path_finder;
 pub mod perimeter;
@@ -34,6 +36,7 @@ pub mod simple_assembler;
 pub mod simple_graph;
 pub mod snail;
 pub mod social_golfer;
+pub mod solver;
 pub mod spiralize;
 pub mod string_incrementer;
 pub mod sudoku_solver;
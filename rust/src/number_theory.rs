@@ -0,0 +1,222 @@
+//! Primality testing and integer factorization of `u64` numbers
+//!
+//! Primality is decided by the [Miller–Rabin test](https://en.wikipedia.org/wiki/Miller%E2%80%93Rabin_primality_test)
+//! which is deterministic for all `u64` numbers when the first 12 primes are used as witnesses.
+//! Composite numbers are split by [Pollard's rho algorithm](https://en.wikipedia.org/wiki/Pollard%27s_rho_algorithm)
+//! with [Brent's cycle detection](https://doi.org/10.1007/BF01933190), which finds a factor `p` in
+//! `O(sqrt(p))` expected steps. All the modular multiplications are carried out in `u128` so that
+//! they can't overflow.
+
+use std::collections::BTreeMap;
+
+use num::integer::gcd;
+
+/// The first 12 primes, which are sufficient witnesses for any `n < 3.3 * 10^24`
+const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Number of steps of Pollard's rho between two GCD computations
+const BATCH: u64 = 128;
+
+/// Computes `a * b (mod m)`
+#[inline]
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+/// Computes `b^e (mod m)` by repeated squaring
+fn pow_mod(mut b: u64, mut e: u64, m: u64) -> u64 {
+    let mut pow = 1 % m;
+    b %= m;
+    while e > 0 {
+        if e & 1 == 1 {
+            pow = mul_mod(pow, b, m);
+        }
+        b = mul_mod(b, b, m);
+        e >>= 1;
+    }
+    pow
+}
+
+/// Returns `true` iff `n` is a prime number
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+
+    for p in WITNESSES {
+        if n % p == 0 {
+            return n == p;
+        }
+    }
+
+    // n - 1 = d * 2^s where d is odd
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    WITNESSES.iter().all(|&a| {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Finds a non-trivial factor of an odd composite `n` by Pollard's rho algorithm with Brent's
+/// cycle detection
+///
+/// The pseudo-random sequence `x -> x^2 + c (mod n)` is restarted with the next `c` in the
+/// (unlikely) case that the cycle is found without revealing a factor.
+fn rho(n: u64) -> u64 {
+    debug_assert!(n % 2 == 1 && !is_prime(n), "{n} is not an odd composite");
+
+    for c in 1..n {
+        let f = |x: u64| ((mul_mod(x, x, n) as u128 + c as u128) % n as u128) as u64;
+
+        let (mut x, mut y, mut ys) = (2, 2, 2);
+        let (mut q, mut g, mut r) = (1, 1, 1);
+
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+
+            // Accumulate the differences in batches so that GCD is computed only once per batch
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..BATCH.min(r - k) {
+                    y = f(y);
+                    q = mul_mod(q, x.abs_diff(y), n);
+                }
+                g = gcd(q, n);
+                k += BATCH;
+            }
+
+            r *= 2;
+        }
+
+        // The batch overshot, so backtrack step by step from its start
+        if g == n {
+            loop {
+                ys = f(ys);
+                g = gcd(x.abs_diff(ys), n);
+                if g > 1 {
+                    break;
+                }
+            }
+        }
+
+        if g != n {
+            return g;
+        }
+    }
+
+    unreachable!("{n} has no non-trivial factor")
+}
+
+/// Returns the prime factorization of `n` as pairs `(p, exponent)` ordered by the primes `p`
+///
+/// Note that `0` and `1` have no prime factors, so the result is empty for them.
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    let mut factors = BTreeMap::new();
+
+    if n < 2 {
+        return Vec::new();
+    }
+
+    // Small factors are cheaper to divide out directly
+    let mut n = n;
+    for p in WITNESSES {
+        while n % p == 0 {
+            *factors.entry(p).or_insert(0) += 1;
+            n /= p;
+        }
+    }
+
+    let mut composites = if n > 1 { vec![n] } else { Vec::new() };
+    while let Some(n) = composites.pop() {
+        if is_prime(n) {
+            *factors.entry(n).or_insert(0) += 1;
+        } else {
+            let d = rho(n);
+            composites.push(d);
+            composites.push(n / d);
+        }
+    }
+
+    factors.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primes;
+    use rstest::*;
+
+    use std::time::Duration;
+
+    #[rstest]
+    #[case(0, false)]
+    #[case(1, false)]
+    #[case(2, true)]
+    #[case(37, true)]
+    #[case(41, true)]
+    #[case(561, false)] // Carmichael number
+    #[case(1_000_000_007, true)]
+    #[case(3_215_031_751, false)] // strong pseudoprime to bases 2, 3, 5 and 7
+    #[case(3_825_123_056_546_413_051, false)] // strong pseudoprime to bases 2, 3, ..., 23
+    #[case(4_294_967_291, true)]
+    #[case(4_294_967_297, false)] // F5 = 641 * 6700417
+    #[case(18_446_744_073_709_551_557, true)]
+    #[case(u64::MAX, false)]
+    #[trace]
+    fn primality(#[case] n: u64, #[case] expected: bool) {
+        assert_eq!(expected, is_prime(n));
+    }
+
+    #[test]
+    fn same_as_stream() {
+        let expected = primes::stream().take_while(|&p| p < 100_000);
+        let actual = (0..100_000).filter(|&n| is_prime(n as u64));
+        assert!(expected.eq(actual));
+    }
+
+    #[rstest]
+    #[case(0, vec![])]
+    #[case(1, vec![])]
+    #[case(2, vec![(2, 1)])]
+    #[case(360, vec![(2, 3), (3, 2), (5, 1)])]
+    #[case(600_851_475_143, vec![(71, 1), (839, 1), (1471, 1), (6857, 1)])]
+    #[case(998_244_353 * 1_000_000_007, vec![(998_244_353, 1), (1_000_000_007, 1)])]
+    #[case(18_446_743_979_220_271_189, vec![(4_294_967_279, 1), (4_294_967_291, 1)])]
+    #[case(4_294_967_291 * 4_294_967_291, vec![(4_294_967_291, 2)])]
+    #[case(
+        u64::MAX,
+        vec![(3, 1), (5, 1), (17, 1), (257, 1), (641, 1), (65_537, 1), (6_700_417, 1)]
+    )]
+    #[case(1 << 63, vec![(2, 63)])]
+    #[case(3_825_123_056_546_413_051, vec![(149_491, 1), (747_451, 1), (34_233_211, 1)])]
+    #[timeout(Duration::from_secs(1))]
+    #[trace]
+    fn factorization(#[case] n: u64, #[case] expected: Vec<(u64, u32)>) {
+        assert_eq!(expected, factorize(n));
+    }
+
+    #[test]
+    fn product_of_factors() {
+        for n in (1..10_000).chain(u64::MAX - 10_000..=u64::MAX) {
+            let factors = factorize(n);
+            assert!(factors.iter().all(|&(p, _)| is_prime(p)), "{n}");
+            let product = factors.iter().map(|&(p, e)| p.pow(e)).product::<u64>();
+            assert_eq!(n, product);
+        }
+    }
+}
@@ -3,10 +3,32 @@ use std::cmp::{Ordering, Reverse};
 use std::collections::binary_heap::{self, BinaryHeap};
 use std::collections::hash_map::{Entry, HashMap};
 
-use num::integer::{gcd, sqrt};
-use num::CheckedMul;
+use num::integer::{gcd, sqrt, Roots};
+use num::{CheckedMul, PrimInt, Unsigned};
 
-/// Returns an infinite stream of prime numbers
+mod counting;
+mod segmented;
+
+pub use crate::number_theory::is_prime;
+pub use counting::{nth_prime, prime_pi};
+pub use segmented::primes_in;
+
+/// Unsigned integer types in which the prime [stream_of] can be generated
+pub trait PrimeInt: PrimInt + Unsigned + Roots + From<u32> {}
+
+impl PrimeInt for u32 {}
+impl PrimeInt for u64 {}
+
+/// Returns a stream of all `u32` prime numbers (see [stream_of])
+#[inline]
+pub fn stream() -> impl Iterator<Item = u32> {
+    stream_of()
+}
+
+/// Returns a stream of prime numbers which ends with the last prime representable by `T`
+///
+/// Note that the sieve keeps a lazy list of multiples for each prime up to `sqrt(T::MAX)`, so for
+/// `u64` its memory grows with every produced prime. Use [primes_in] for ranges of large primes.
 ///
 /// The implementation is based on the following papers:
 ///  - [_The Genuine Sieve of Eratosthenes_][sieve] for the sieve algorithm
@@ -14,9 +36,9 @@ use num::CheckedMul;
 ///
 /// [sieve]: https://doi.org/10.1017/S0956796808007004
 /// [wheel]: https://research.cs.wisc.edu/techreports/1990/TR909.pdf
-pub fn stream() -> impl Iterator<Item = u32> {
-    let Wheel { primes, spin } = Wheel::<8, _>::new();
-    primes.into_iter().chain(Sieve::new(spin))
+pub fn stream_of<T: PrimeInt>() -> impl Iterator<Item = T> {
+    let Wheel { primes, spin } = Wheel::<8, T, _>::new();
+    primes.into_iter().map(Into::into).chain(Sieve::new(spin))
 }
 
 /// The [`Wheel`] optimizes the input to the [`Sieve`] as follows:
@@ -30,14 +52,16 @@ pub fn stream() -> impl Iterator<Item = u32> {
 /// an  iterator called [`Spin`].
 ///
 /// Note that the individual steps of the [`Spin`] (not to be confused with the produced numbers)
-/// are represented as `u16`, which should be sufficient range for [gaps between two consecutive
-/// `u32` prime numbers](https://en.wikipedia.org/wiki/Prime_gap).
-struct Wheel<'s, const N: usize, S: Iterator<Item = &'s u16>> {
+/// are represented as `u16` regardless of the type `T` of the produced numbers. The steps are gaps
+/// between consecutive numbers coprime to the wheel primes, which are much smaller than the
+/// [gaps between two consecutive primes](https://en.wikipedia.org/wiki/Prime_gap) (e.g. at most
+/// `34` for the 8 primes of [stream_of]), and are widened to `T` only when added up.
+struct Wheel<'s, const N: usize, T, S: Iterator<Item = &'s u16>> {
     primes: [u32; N],
-    spin: Spin<S>,
+    spin: Spin<S, T>,
 }
 
-impl<const N: usize> Wheel<'static, N, std::slice::Iter<'static, u16>> {
+impl<const N: usize, T: PrimeInt> Wheel<'static, N, T, std::slice::Iter<'static, u16>> {
     fn new() -> Self {
         assert!(N > 0, "N cannot be 0");
 
@@ -76,7 +100,7 @@ impl<const N: usize> Wheel<'static, N, std::slice::Iter<'static, u16>> {
         // the very first item is the next prime p - 1, so initialize n to 1 to offset this
         let spin = Spin {
             steps: wheel.iter().cycle(),
-            n: 1,
+            n: Some(T::one()),
         };
 
         Self { primes, spin }
@@ -102,22 +126,27 @@ impl<const N: usize> Wheel<'static, N, std::slice::Iter<'static, u16>> {
     }
 }
 
+/// Note that the spin ends (instead of overflowing) with the last number representable by `T`
 #[derive(Clone)]
-struct Spin<I> {
+struct Spin<I, T> {
     steps: std::iter::Cycle<I>,
-    n: u32,
+    /// The last yielded number, or `None` once the spin has ended
+    n: Option<T>,
 }
 
-impl<'s, I> Iterator for Spin<I>
+impl<'s, I, T> Iterator for Spin<I, T>
 where
     I: Iterator<Item = &'s u16> + Clone,
+    T: PrimeInt,
 {
-    type Item = u32;
+    type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let n = self.n?;
         // SAFETY: self.steps is a Cycle
-        self.n += unsafe { *self.steps.next().unwrap_unchecked() } as u32;
-        Some(self.n)
+        let step = unsafe { *self.steps.next().unwrap_unchecked() };
+        self.n = n.checked_add(&(step as u32).into());
+        self.n
     }
 }
 
@@ -146,26 +175,27 @@ where
     }
 }
 
-type TableEntry<I> = Reverse<List<u32, MulBy<I, u32>>>;
+type TableEntry<I, T> = Reverse<List<T, MulBy<I, T>>>;
 
-struct Sieve<I> {
-    x: Option<u32>,
+struct Sieve<I, T> {
+    x: Option<T>,
     xs: I,
     // NOTE: this is a min-heap, not a max-heap of reversed lists
-    table: BinaryHeap<TableEntry<I>>,
-    max: u32,
+    table: BinaryHeap<TableEntry<I, T>>,
+    max: T,
 }
 
-impl<I> Sieve<I>
+impl<I, T> Sieve<I, T>
 where
-    I: Iterator<Item = u32> + Clone,
+    I: Iterator<Item = T> + Clone,
+    T: PrimeInt,
 {
     fn new(mut xs: I) -> Self {
         let mut this = Self {
             x: xs.next(),
             xs,
             table: BinaryHeap::new(),
-            max: sqrt(u32::MAX),
+            max: sqrt(T::max_value()),
         };
 
         if let Some(x) = this.x {
@@ -177,10 +207,10 @@ where
 
     /// Registers new lazy [`List`] of multiples of a prime `p` in the [`table`](Self::table)
     ///
-    /// Note that since we insert `[p^2, ...]` into the table, we ignore any `p > sqrt(u32::MAX)`.
-    /// This is fine, because we can never get to them under the `u32` prime representation (see
+    /// Note that since we insert `[p^2, ...]` into the table, we ignore any `p > sqrt(T::MAX)`.
+    /// This is fine, because we can never get to them under the `T` prime representation (see
     /// the [`is_composite`](Self::is_composite) check).
-    fn insert_prime(&mut self, p: u32) {
+    fn insert_prime(&mut self, p: T) {
         if p > self.max {
             return;
         }
@@ -193,12 +223,12 @@ where
 
     /// Returns `true` iff the first entry's head `n` satisfies `n <= x`
     #[inline]
-    fn is_composite(&self, x: u32) -> bool {
+    fn is_composite(&self, x: T) -> bool {
         matches!(self.table.peek(), Some(Reverse(List { head, .. })) if *head <= x)
     }
 
     /// Remove all numbers `n` stored in the [`table`](Self::table) such that `n <= x`
-    fn adjust(&mut self, x: u32) {
+    fn adjust(&mut self, x: T) {
         // stop when we've reached x
         while self.is_composite(x) {
             // take out the first list, advance it, and re-insert the rest back
@@ -213,18 +243,19 @@ where
             match tail.next() {
                 Some(h) => *head = h,
                 None => {
-                    let _ = binary_heap::PeekMut::<'_, TableEntry<I>>::pop(list);
+                    let _ = binary_heap::PeekMut::<'_, TableEntry<I, T>>::pop(list);
                 }
             }
         }
     }
 }
 
-impl<I> Iterator for Sieve<I>
+impl<I, T> Iterator for Sieve<I, T>
 where
-    I: Iterator<Item = u32> + Clone,
+    I: Iterator<Item = T> + Clone,
+    T: PrimeInt,
 {
-    type Item = u32;
+    type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
         if let x @ Some(_) = self.x.take() {
@@ -297,7 +328,7 @@ mod tests {
     #[timeout(Duration::from_millis(100))]
     #[trace]
     fn init_primes<const N: usize>(#[case] expected: [u32; N]) {
-        assert_eq!(expected, Wheel::primes());
+        assert_eq!(expected, Wheel::<N, u32, _>::primes());
     }
 
     #[rstest]
@@ -306,7 +337,7 @@ mod tests {
     #[case([2, 3, 5], &[7, 11, 13, 17, 19, 23, 29, 31, 37, 41])] // [6, 4, 2, 4, 2, 4, 6, 2]
     #[trace]
     fn small_wheels<const N: usize>(#[case] expected_primes: [u32; N], #[case] expected: &[u32]) {
-        let Wheel { primes, spin } = Wheel::<N, _>::new();
+        let Wheel { primes, spin } = Wheel::<N, u32, _>::new();
         let actual = spin.take(expected.len()).collect::<Vec<_>>();
         assert_eq!(expected_primes, primes);
         assert_eq!(expected, actual);
@@ -345,4 +376,35 @@ mod tests {
     fn large_prime(#[case] n: usize, #[case] expected: u32) {
         assert_eq!(Some(expected), stream().nth(n - 1));
     }
+
+    #[test]
+    fn same_as_u32() {
+        let expected = stream().take(100_000).map(u64::from).collect::<Vec<_>>();
+        let actual = stream_of::<u64>().take(100_000).collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn spin_ends_at_max() {
+        // The spin of wheel [2, 3, 5] has to start at a number congruent to 1 (mod 30)
+        let start = u32::MAX - 44;
+
+        let Wheel { spin, .. } = Wheel::<3, u32, _>::new();
+        let mut spin = Spin {
+            n: Some(start),
+            ..spin
+        };
+        assert_eq!(spin.by_ref().last(), Some(u32::MAX - 2));
+        // The spin must not resume with a number off the wheel (e.g. u32::MAX)
+        assert_eq!(spin.next(), None);
+        assert_eq!(spin.next(), None);
+
+        let Wheel { spin, .. } = Wheel::<3, u64, _>::new();
+        let spin = Spin {
+            n: Some(start as u64),
+            ..spin
+        };
+        let expected = [7, 11, 13, 17, 19, 23, 29, 31, 37, 41].map(|k| start as u64 - 1 + k);
+        assert_eq!(spin.take(10).collect::<Vec<_>>(), expected);
+    }
 }
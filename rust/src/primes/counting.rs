@@ -0,0 +1,236 @@
+//! Prime counting function `π(x)` and the `n`-th prime in sublinear time
+//!
+//! [Legendre's formula](https://en.wikipedia.org/wiki/Prime-counting_function#Algorithms_for_evaluating_%CF%80(x))
+//! counts primes up to `x` as `π(x) = φ(x, a) + a - 1` where `a = π(sqrt(x))` and `φ(x, a)` is the
+//! number of integers in `[1, x]` which are not divisible by any of the first `a` primes.
+//! [Lehmer's extension](https://doi.org/10.1215/ijm/1255455259) of Meissel's method reduces `a` to
+//! `π(x^(1/4))` by subtracting the numbers with two or three larger prime factors, which are in
+//! turn counted by `π` of smaller arguments.
+
+use std::collections::HashMap;
+
+use num::integer::{gcd, Roots};
+
+use super::primes_in;
+
+/// Number of the first primes for which `φ(x, a)` is looked up in a table of the wheel
+const WHEEL: usize = 6;
+
+/// Primes below this bound are counted directly by the sieve
+const SMALL: u64 = 1 << 16;
+
+/// Returns the number of primes less than or equal to `x`
+pub fn prime_pi(x: u64) -> u64 {
+    if x < SMALL {
+        return primes_in(0..x + 1).count() as u64;
+    }
+    Lehmer::new(x).pi(x)
+}
+
+/// Returns the `n`-th prime (counting from `p_1 = 2`) or `None` for `n = 0`
+///
+/// The prime is first estimated by the asymptotic expansion of `p_n` due to
+/// [Cipolla](https://doi.org/10.1007/BF01449909), then the primes up to the estimate are counted
+/// by [prime_pi] and finally the remaining primes between the estimate and `p_n` are sieved by
+/// [primes_in](super::primes_in).
+pub fn nth_prime(n: u64) -> Option<u64> {
+    const SEGMENT: u64 = 1 << 20;
+
+    if n == 0 {
+        return None;
+    }
+
+    // p_n < n (ln(n) + ln(ln(n))) holds for n >= 6 (and p_1000 = 7919)
+    if n <= 1000 {
+        return primes_in(0..7920).nth(n as usize - 1);
+    }
+
+    let x = estimate(n);
+    let count = prime_pi(x);
+
+    if count < n {
+        let n_f64 = n as f64;
+        let end = (n_f64 * (n_f64.ln() + n_f64.ln().ln())) as u64;
+        return primes_in(x + 1..end).nth((n - count - 1) as usize);
+    }
+
+    // Search backwards segment by segment, there are `count` primes less than `hi`
+    let (mut hi, mut count) = (x + 1, count);
+    loop {
+        let lo = hi.saturating_sub(SEGMENT);
+        let primes = primes_in(lo..hi).collect::<Vec<_>>();
+        let below = count - primes.len() as u64;
+        if below < n {
+            return Some(primes[(n - below - 1) as usize]);
+        }
+        (hi, count) = (lo, below);
+    }
+}
+
+/// Estimates the `n`-th prime by the first terms of Cipolla's asymptotic expansion
+fn estimate(n: u64) -> u64 {
+    let n = n as f64;
+    let (ln, lnln) = (n.ln(), n.ln().ln());
+    let p =
+        n * (ln + lnln - 1. + (lnln - 2.) / ln - (lnln * lnln - 6. * lnln + 11.) / (2. * ln * ln));
+    p as u64
+}
+
+/// Evaluation of `π(x)` by Lehmer's formula
+struct Lehmer {
+    /// All the primes up to `sqrt(x)`
+    primes: Vec<u64>,
+    /// Upper bound (exclusive) on the sieved primes
+    bound: u64,
+    /// Tables of `φ(r, a)` for residues `r` modulo the product of the first `a <= WHEEL` primes
+    wheels: Vec<Vec<u32>>,
+    /// Memoized values of `φ(x, a)`
+    phi: HashMap<(u64, usize), u64>,
+}
+
+impl Lehmer {
+    fn new(x: u64) -> Self {
+        let bound = x.sqrt().max(SMALL) + 1;
+        let primes = primes_in(0..bound).collect::<Vec<_>>();
+
+        let mut wheels = vec![Vec::new()];
+        let mut modulus = 1;
+        for &p in &primes[..WHEEL] {
+            modulus *= p as usize;
+            let table = (0..modulus)
+                .scan(0, |count, r| {
+                    if gcd(r, modulus) == 1 {
+                        *count += 1;
+                    }
+                    Some(*count)
+                })
+                .collect();
+            wheels.push(table);
+        }
+
+        Self {
+            primes,
+            bound,
+            wheels,
+            phi: HashMap::new(),
+        }
+    }
+
+    /// Returns `π(x)`, which is either a look up of the sieved primes or Lehmer's formula
+    fn pi(&mut self, x: u64) -> u64 {
+        if x < self.bound {
+            return self.primes.partition_point(|&p| p <= x) as u64;
+        }
+
+        let a = self.pi(x.nth_root(4)) as usize;
+        let b = self.pi(x.sqrt()) as usize;
+        let c = self.pi(x.cbrt()) as usize;
+
+        // φ(x, a) + (b + a - 2) (b - a + 1) / 2
+        let mut sum = self.phi(x, a) + ((b + a - 2) * (b - a + 1) / 2) as u64;
+
+        for i in a..b {
+            let w = x / self.primes[i];
+            sum -= self.pi(w);
+
+            if i < c {
+                let bi = self.pi(w.sqrt()) as usize;
+                for j in i..bi {
+                    sum -= self.pi(w / self.primes[j]) - j as u64;
+                }
+            }
+        }
+
+        sum
+    }
+
+    /// Returns `φ(x, a)`, the number of integers in `[1, x]` that are not divisible by any of the
+    /// first `a` primes, by the recurrence `φ(x, a) = φ(x, a - 1) - φ(x / p_a, a - 1)`
+    fn phi(&mut self, x: u64, a: usize) -> u64 {
+        if a == 0 {
+            return x;
+        }
+        if a <= WHEEL {
+            let table = &self.wheels[a];
+            let modulus = table.len() as u64;
+            let totient = *table.last().expect("non-empty table") as u64;
+            return x / modulus * totient + table[(x % modulus) as usize] as u64;
+        }
+
+        let p = self.primes[a - 1];
+        if x <= p {
+            return (x > 0) as u64;
+        }
+        if p * p > x && x < self.bound {
+            // Only 1 and the primes in (p_a, x] remain
+            return self.pi(x) - a as u64 + 1;
+        }
+
+        if let Some(&phi) = self.phi.get(&(x, a)) {
+            return phi;
+        }
+
+        let phi = self.phi(x, a - 1) - self.phi(x / p, a - 1);
+        self.phi.insert((x, a), phi);
+        phi
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    use std::time::Duration;
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(1, 0)]
+    #[case(2, 1)]
+    #[case(10, 4)]
+    #[case(100, 25)]
+    #[case(1_000, 168)]
+    #[case(65_536, 6_542)]
+    #[case(1_000_000, 78_498)]
+    #[case(1_000_000_000, 50_847_534)]
+    #[case(10_000_000_000, 455_052_511)]
+    #[case(100_000_000_000, 4_118_054_813)]
+    #[timeout(Duration::from_secs(10))]
+    #[trace]
+    fn counting(#[case] x: u64, #[case] expected: u64) {
+        assert_eq!(expected, prime_pi(x));
+    }
+
+    #[test]
+    fn same_as_sieve() {
+        for x in (SMALL - 10..SMALL + 10).chain((100_000..2_000_000).step_by(99_991)) {
+            assert_eq!(primes_in(0..x + 1).count() as u64, prime_pi(x), "pi({x})");
+        }
+    }
+
+    #[rstest]
+    #[case(0, None)]
+    #[case(1, Some(2))]
+    #[case(2, Some(3))]
+    #[case(6, Some(13))]
+    #[case(1_000, Some(7_919))]
+    #[case(1_001, Some(7_927))]
+    #[case(10_000, Some(104_729))]
+    #[case(1_000_000, Some(15_485_863))]
+    #[case(64_955_634, Some(1_294_268_491))]
+    #[case(100_000_000, Some(2_038_074_743))]
+    #[case(1_000_000_000, Some(22_801_763_489))]
+    #[timeout(Duration::from_secs(10))]
+    #[trace]
+    fn nth(#[case] n: u64, #[case] expected: Option<u64>) {
+        assert_eq!(expected, nth_prime(n));
+    }
+
+    #[test]
+    fn same_as_stream() {
+        let primes = super::super::stream().take(200_000).collect::<Vec<_>>();
+        for n in (1_000..200_000).step_by(9_973) {
+            assert_eq!(Some(primes[n - 1] as u64), nth_prime(n as u64), "p_{n}");
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Segmented [Sieve of Eratosthenes](https://en.wikipedia.org/wiki/Sieve_of_Eratosthenes#Segmented_sieve)
+//!
+//! Unlike the incremental [stream](super::stream), which has to generate all the primes from `2`
+//! on, the segmented sieve starts right at the beginning of a range. It first collects the
+//! *sieving primes* up to `sqrt(end)` (recursively, by the segmented sieve itself) and then
+//! crosses off their multiples in consecutive segments of fixed size.
+//!
+//! The memory used for a segment is bounded independently of the range, but the sieving primes
+//! are kept for the whole iteration. These take 4 bytes per prime, i.e. about `4 * π(sqrt(end))`
+//! bytes, which is about 14 KB for `end = 10^9`, but over 800 MB for ranges just below `u64::MAX`
+//! (where there are more than `2 * 10^8` sieving primes).
+//!
+//! Only the candidates that pass through the [`Wheel`] are tested against a segment, which also
+//! means that multiples of the wheel primes never have to be crossed off.
+
+use std::ops::Range;
+
+use num::{integer::Roots, Integer};
+
+use super::{Spin, Wheel};
+
+/// Number of integers covered by a single segment
+const SEGMENT: u64 = 1 << 18;
+
+/// Number of wheel primes (the same wheel as the one of [stream](super::stream))
+const WHEEL: usize = 8;
+
+/// Returns an iterator over all the primes in given range in the ascending order
+pub fn primes_in(Range { start, end }: Range<u64>) -> impl Iterator<Item = u64> {
+    let Wheel { primes, spin } = Wheel::<WHEEL, u64, _>::new();
+    let modulus = primes.iter().map(|&p| p as u64).product();
+
+    let small = primes
+        .iter()
+        .map(|&p| p as u64)
+        .filter(|p| (start..end).contains(p))
+        .collect::<Vec<_>>();
+
+    // Sieving primes up to sqrt(end - 1), except for the wheel primes (note that the recursion
+    // ends as soon as there are no such primes, since the range shrinks to its square root)
+    let limit = end.saturating_sub(1).sqrt();
+    let largest = primes[WHEEL - 1] as u64;
+    let sieving = if limit > largest {
+        primes_in(largest + 1..limit + 1)
+            .map(|p| p as u32)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Segmented {
+        small: small.into_iter(),
+        candidates: candidates(spin.steps, modulus, start),
+        sieving,
+        composite: Vec::with_capacity(SEGMENT as usize),
+        lo: start,
+        end,
+    }
+}
+
+/// Returns all the numbers coprime to the wheel primes greater than or equal to `start` (except
+/// for `1`), i.e. the [Spin] of given `steps` positioned to `start`
+fn candidates<'s, I>(
+    steps: std::iter::Cycle<I>,
+    modulus: u64,
+    start: u64,
+) -> impl Iterator<Item = u64>
+where
+    I: Iterator<Item = &'s u16> + Clone,
+{
+    // The wheel is aligned at 1 (mod modulus), so restart it at the last such number before start
+    let n = start - start % modulus + 1;
+    let first = Some(n).filter(|&n| n > 1);
+    first
+        .into_iter()
+        .chain(Spin { steps, n: Some(n) })
+        .skip_while(move |&n| n < start)
+}
+
+struct Segmented<C> {
+    /// Wheel primes which fall into the range
+    small: std::vec::IntoIter<u64>,
+    candidates: C,
+    /// Sieving primes (which are less than `2^32` as their squares are less than `end`)
+    sieving: Vec<u32>,
+    /// Composites in the current segment `[lo, lo + composite.len())`
+    composite: Vec<bool>,
+    lo: u64,
+    end: u64,
+}
+
+impl<C> Segmented<C> {
+    /// Sieves the segment starting at `lo`
+    fn sieve(&mut self, lo: u64) {
+        let hi = lo.saturating_add(SEGMENT).min(self.end);
+
+        self.lo = lo;
+        self.composite.clear();
+        self.composite.resize((hi - lo) as usize, false);
+
+        for &p in &self.sieving {
+            let p = u64::from(p);
+            if p * p >= hi {
+                // Sieving primes are sorted, so the rest does not affect this segment either
+                break;
+            }
+
+            // The first multiple in this segment, where those below `p^2` have smaller factors
+            let mut next = Integer::div_ceil(&lo, &p).saturating_mul(p).max(p * p);
+            while next < hi {
+                self.composite[(next - lo) as usize] = true;
+                next = next.saturating_add(p);
+            }
+        }
+    }
+}
+
+impl<C: Iterator<Item = u64>> Iterator for Segmented<C> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let p @ Some(_) = self.small.next() {
+            return p;
+        }
+
+        loop {
+            let n = self.candidates.next().filter(|&n| n < self.end)?;
+
+            if n >= self.lo + self.composite.len() as u64 {
+                self.sieve(n);
+            }
+
+            if !self.composite[(n - self.lo) as usize] {
+                return Some(n);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::stream;
+    use super::*;
+    use rstest::*;
+
+    use std::time::Duration;
+
+    #[rstest]
+    #[case(0..0, &[])]
+    #[case(0..30, &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29])]
+    #[case(13..24, &[13, 17, 19, 23])]
+    #[case(7900..8020, &[7901, 7907, 7919, 7927, 7933, 7937, 7949, 7951, 7963, 7993, 8009, 8011, 8017])]
+    #[case(9_699_680..9_699_720, &[9_699_713])]
+    #[case(1_000_000_000..1_000_000_030, &[1_000_000_007, 1_000_000_009, 1_000_000_021])]
+    #[case(
+        1_000_000_000_000_000..1_000_000_000_000_200,
+        &[1_000_000_000_000_037, 1_000_000_000_000_091, 1_000_000_000_000_159, 1_000_000_000_000_187]
+    )]
+    #[timeout(Duration::from_secs(5))]
+    #[trace]
+    fn ranges(#[case] range: Range<u64>, #[case] expected: &[u64]) {
+        assert_eq!(expected, primes_in(range).collect::<Vec<_>>());
+    }
+
+    #[rstest]
+    #[case(0..100_000)]
+    #[case(1..SEGMENT + 1)]
+    #[case(123_456..3 * SEGMENT + 17)]
+    #[trace]
+    fn same_as_stream(#[case] range: Range<u64>) {
+        let expected = stream()
+            .map(|p| p as u64)
+            .skip_while(|p| *p < range.start)
+            .take_while(|p| *p < range.end)
+            .collect::<Vec<_>>();
+
+        assert_eq!(expected, primes_in(range).collect::<Vec<_>>());
+    }
+
+    #[rstest]
+    #[case(1_000_000_000..1_001_000_000, 48_155)]
+    #[case(1_000_000_000_000..1_000_001_000_000, 36_249)]
+    #[timeout(Duration::from_secs(3))]
+    #[trace]
+    fn bench_range(#[case] range: Range<u64>, #[case] count: usize) {
+        assert_eq!(count, primes_in(range).count());
+    }
+
+    /// The [stream](super::stream) has to generate more than 50 million primes below the range
+    #[ignore]
+    #[rstest]
+    #[case(1_000_000_000..1_001_000_000)]
+    #[trace]
+    fn bench_range_stream(#[case] range: Range<u64>) {
+        let expected = stream()
+            .map(|p| p as u64)
+            .skip_while(|p| *p < range.start)
+            .take_while(|p| *p < range.end)
+            .collect::<Vec<_>>();
+
+        assert_eq!(expected, primes_in(range).collect::<Vec<_>>());
+    }
+
+    /// Compare with the [stream](super::stream) bench which takes the first `n` primes
+    #[rstest]
+    #[case(1_000)]
+    #[case(10_000)]
+    #[case(100_000)]
+    #[case(1_000_000)]
+    #[timeout(Duration::from_secs(12))]
+    #[trace]
+    fn bench(#[case] n: usize) {
+        // Upper bound on the n-th prime for n >= 6
+        let n_f64 = n as f64;
+        let end = (n_f64 * (n_f64.ln() + n_f64.ln().ln())) as u64;
+        assert!(primes_in(0..end).take(n).count() == n);
+    }
+}
@@ -0,0 +1,259 @@
+//! Root finding of single-variable expressions by
+//! [Newton's method](https://en.wikipedia.org/wiki/Newton%27s_method) with symbolic derivatives.
+//!
+//! Newton's method converges quadratically near simple roots but might diverge (or cycle) when
+//! started too far from a root. So whenever the root is *bracketed* (i.e. the function changes
+//! sign on the search interval), the solver keeps track of the bracket and falls back to a
+//! [bisection](https://en.wikipedia.org/wiki/Bisection_method) step each time a Newton step
+//! leaves the bracket, fails to decrease `|f(x)|` or can't be evaluated.
+use crate::differentiation::{DiffError, Expr, Notation};
+use std::ops::RangeInclusive;
+
+/// Outcome of the root finding
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// The step size or the function value dropped below given tolerance
+    Converged,
+    /// The maximum number of iterations was exhausted before convergence
+    MaxIterations,
+    /// Newton's method left the search interval (or hit a stationary point) and there is no
+    /// bracket to fall back to bisection, or the expression can't be evaluated at the midpoint of
+    /// the interval or of the bracket
+    Diverged,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Solution {
+    /// Last approximation of the root
+    pub root: f64,
+    /// Total number of iterations (both Newton and bisection steps)
+    pub iterations: usize,
+    /// Number of iterations which fell back to bisection
+    pub bisections: usize,
+    pub status: Status,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Solver {
+    /// Absolute tolerance of both the step size and `|f(x)|`
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-12,
+            max_iterations: 100,
+        }
+    }
+}
+
+/// Finds a root of an infix expression in one variable on given interval with default [Solver]
+/// settings (see [Solver::solve]).
+#[inline]
+pub fn find_root(expr: &str, interval: RangeInclusive<f64>) -> Result<Solution, DiffError> {
+    Solver::default().solve(expr, interval)
+}
+
+impl Solver {
+    /// Finds a root of an infix expression `f` in one variable on given interval, starting from
+    /// its midpoint.
+    ///
+    /// Fails if the expression can't be parsed or differentiated (in particular if it has more
+    /// than one variable) or if it can't be evaluated at the interval bounds.
+    pub fn solve(&self, expr: &str, interval: RangeInclusive<f64>) -> Result<Solution, DiffError> {
+        let f = Notation::Infix.parse(expr)?;
+
+        let var = {
+            let vars = f.vars();
+            let mut vars = vars.into_iter();
+            match (vars.next(), vars.next()) {
+                (Some(var), None) => var.to_owned(),
+                (None, _) => "x".to_owned(),
+                (Some(_), Some(_)) => return Err(DiffError::AmbiguousVariable(f.to_string())),
+            }
+        };
+
+        let df = f.derivative(&var)?.simplified();
+        let eval = |e: &Expr, x: f64| e.eval(&[(var.as_str(), x)]);
+
+        let (mut a, mut b) = interval.into_inner();
+        let (fa, fb) = (eval(&f, a)?, eval(&f, b)?);
+
+        let solution = |root, iterations, bisections, status| Solution {
+            root,
+            iterations,
+            bisections,
+            status,
+        };
+
+        if fa.abs() <= self.tolerance {
+            return Ok(solution(a, 0, 0, Status::Converged));
+        }
+        if fb.abs() <= self.tolerance {
+            return Ok(solution(b, 0, 0, Status::Converged));
+        }
+
+        // Orient the bracket so that `f(a) < 0 < f(b)`
+        let bracketed = fa.signum() != fb.signum();
+        if bracketed && fa > 0.0 {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        let (lo, hi) = (a.min(b), a.max(b));
+        let mut x = 0.5 * (a + b);
+        let mut fx = match eval(&f, x) {
+            Ok(fx) => fx,
+            Err(_) => return Ok(solution(x, 0, 0, Status::Diverged)),
+        };
+        let mut bisections = 0;
+
+        for iteration in 1..=self.max_iterations {
+            if fx.abs() <= self.tolerance {
+                return Ok(solution(x, iteration - 1, bisections, Status::Converged));
+            }
+
+            // Shrink the bracket around the current approximation
+            if bracketed {
+                if fx < 0.0 {
+                    a = x;
+                } else {
+                    b = x;
+                }
+            }
+
+            // Newton step, or `None` if it's not admissible
+            let newton = eval(&df, x)
+                .ok()
+                .filter(|dfx| *dfx != 0.0)
+                .map(|dfx| x - fx / dfx)
+                .filter(|next| next.is_finite() && (lo..=hi).contains(next))
+                .and_then(|next| eval(&f, next).ok().map(|f_next| (next, f_next)))
+                .filter(|(_, f_next)| f_next.abs() < fx.abs());
+
+            let (next, f_next) = match newton {
+                Some(step) => step,
+                None if bracketed => {
+                    bisections += 1;
+                    let mid = 0.5 * (a + b);
+                    match eval(&f, mid) {
+                        Ok(f_mid) => (mid, f_mid),
+                        Err(_) => {
+                            return Ok(solution(mid, iteration, bisections, Status::Diverged))
+                        }
+                    }
+                }
+                None => return Ok(solution(x, iteration, bisections, Status::Diverged)),
+            };
+
+            let step = (next - x).abs();
+            x = next;
+            fx = f_next;
+
+            if step <= self.tolerance || fx.abs() <= self.tolerance {
+                return Ok(solution(x, iteration, bisections, Status::Converged));
+            }
+        }
+
+        Ok(solution(
+            x,
+            self.max_iterations,
+            bisections,
+            Status::MaxIterations,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("x^2 - 2", 0.0..=2.0, std::f64::consts::SQRT_2)]
+    #[case("cos(x) - x", 0.0..=1.0, 0.7390851332151607)]
+    #[case("exp(t) - 3", 0.0..=5.0, 3f64.ln())]
+    #[case("x^3 - 6*x^2 + 11*x - 6", 1.5..=2.7, 2.0)]
+    #[case("ln(x) - 1", 0.5..=10.0, std::f64::consts::E)]
+    #[case("x - 1", 1.0..=3.0, 1.0)]
+    #[trace]
+    fn converges(#[case] expr: &str, #[case] interval: RangeInclusive<f64>, #[case] root: f64) {
+        let solution = find_root(expr, interval).unwrap();
+        assert_eq!(solution.status, Status::Converged);
+        assert!((solution.root - root).abs() < 1e-10, "{solution:?}");
+    }
+
+    #[test]
+    fn quadratic_convergence() {
+        let solution = find_root("x^2 - 2", 1.0..=2.0).unwrap();
+        assert_eq!(solution.status, Status::Converged);
+        assert_eq!(solution.bisections, 0);
+        assert!(solution.iterations <= 6, "{solution:?}");
+    }
+
+    #[test]
+    fn bisection_fallback() {
+        // Newton's method diverges from the midpoint 7.5 as `atan` flattens out
+        let solution = find_root("atan(x - 1)", -5.0..=20.0).unwrap();
+        assert_eq!(solution.status, Status::Converged);
+        assert!(solution.bisections > 0, "{solution:?}");
+        assert!((solution.root - 1.0).abs() < 1e-10, "{solution:?}");
+
+        // Newton step to a non-positive `x` can't be evaluated
+        let solution = find_root("ln(x)", 0.001..=100.0).unwrap();
+        assert_eq!(solution.status, Status::Converged);
+        assert!((solution.root - 1.0).abs() < 1e-10, "{solution:?}");
+    }
+
+    #[test]
+    fn divergence() {
+        let solution = find_root("x^2 + 1", -1.0..=1.0).unwrap();
+        assert_eq!(solution.status, Status::Diverged);
+
+        let solution = find_root("atan(x - 1)", 3.0..=20.0).unwrap();
+        assert_eq!(solution.status, Status::Diverged);
+
+        // The midpoint of the interval is a pole
+        let solution = find_root("1 / (x - 1)", 0.0..=2.0).unwrap();
+        assert_eq!(solution.status, Status::Diverged);
+        assert_eq!(solution.iterations, 0);
+
+        // The bisection of the bracket [-3, 1.14...] falls into the gap (-1, 1) of the domain
+        let solution = find_root("x * sqrt(x^2 - 1)", -3.0..=7.0).unwrap();
+        assert_eq!(solution.status, Status::Diverged);
+        assert_eq!(solution.bisections, 1);
+    }
+
+    #[test]
+    fn max_iterations() {
+        let solver = Solver {
+            tolerance: 0.0,
+            max_iterations: 3,
+        };
+        let solution = solver.solve("x^2 - 2", 0.0..=2.0).unwrap();
+        assert_eq!(solution.status, Status::MaxIterations);
+        assert_eq!(solution.iterations, 3);
+    }
+
+    #[rstest]
+    #[case("x + y", 0.0..=1.0, DiffError::AmbiguousVariable("(+ x y)".to_string()))]
+    #[case(
+        "ln(x)",
+        0.0..=1.0,
+        DiffError::Undefined { expr: "(ln x)".to_string(), reason: "for 0".to_string() }
+    )]
+    #[case(
+        "x +",
+        0.0..=1.0,
+        DiffError::Syntax { message: "Unexpected end of expression".to_string(), at: 3 }
+    )]
+    #[trace]
+    fn failures(
+        #[case] expr: &str,
+        #[case] interval: RangeInclusive<f64>,
+        #[case] error: DiffError,
+    ) {
+        assert_eq!(find_root(expr, interval), Err(error));
+    }
+}